@@ -0,0 +1,237 @@
+//! Human-friendly amounts of PCX and the other assets.
+
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+
+use crate::runtime::{
+    primitives::{AssetId, Balance},
+    xpallets::xassets_registrar::AssetInfoOfStoreExt,
+    ChainXClient,
+};
+
+/// The asset id of PCX.
+pub const PCX_ASSET_ID: AssetId = 0;
+/// The symbol of PCX.
+pub const PCX_SYMBOL: &str = "PCX";
+/// The decimals of PCX.
+pub const PCX_DECIMALS: u8 = 8;
+/// One PCX in the raw units.
+pub const PCX: Balance = 100_000_000;
+
+/// An amount given on the command line.
+///
+/// Either the raw units, e.g. `1250000000`, or a decimal number followed by the symbol of
+/// the asset, e.g. `12.5PCX` or `0.001 X-BTC`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Amount {
+    Raw(Balance),
+    Decimal {
+        integer: String,
+        fraction: String,
+        symbol: String,
+    },
+}
+
+impl FromStr for Amount {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let split = s
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(s.len());
+        let (number, symbol) = (&s[..split], s[split..].trim());
+        if number.is_empty() {
+            return Err(format!("Invalid amount `{}`, missing the number", s));
+        }
+
+        if symbol.is_empty() {
+            if number.contains('.') {
+                return Err(format!(
+                    "Invalid amount `{}`, the symbol is required for a decimal amount, e.g. `{}PCX`",
+                    s, number
+                ));
+            }
+            return number
+                .parse()
+                .map(Amount::Raw)
+                .map_err(|err| format!("Invalid amount `{}`: {}", s, err));
+        }
+
+        let mut parts = number.splitn(2, '.');
+        let integer = parts.next().unwrap_or_default();
+        let fraction = parts.next().unwrap_or_default();
+        if fraction.contains('.') || (integer.is_empty() && fraction.is_empty()) {
+            return Err(format!("Invalid amount `{}`", s));
+        }
+        Ok(Amount::Decimal {
+            integer: integer.into(),
+            fraction: fraction.into(),
+            symbol: symbol.into(),
+        })
+    }
+}
+
+impl Amount {
+    /// Returns the raw units of the amount of PCX.
+    pub fn pcx(&self) -> Result<Balance> {
+        self.units(PCX_SYMBOL, PCX_DECIMALS)
+    }
+
+    /// Returns the raw units of the amount of asset `asset_id`.
+    ///
+    /// The decimals of the assets other than PCX are read from `XAssetsRegistrar`.
+    pub async fn asset(&self, client: &ChainXClient, asset_id: AssetId) -> Result<Balance> {
+        match self {
+            Self::Raw(units) => Ok(*units),
+            Self::Decimal { .. } => {
+                let (symbol, decimals) = asset_symbol(client, asset_id).await?;
+                self.units(&symbol, decimals)
+            }
+        }
+    }
+
+    /// Returns the raw units of the amount of asset `symbol` with `decimals`.
+    pub fn units(&self, symbol: &str, decimals: u8) -> Result<Balance> {
+        match self {
+            Self::Raw(units) => Ok(*units),
+            Self::Decimal {
+                integer,
+                fraction,
+                symbol: given,
+            } => {
+                if !given.eq_ignore_ascii_case(symbol) {
+                    return Err(anyhow!("Expected the amount of {}, got {}", symbol, given));
+                }
+                if fraction.len() > decimals as usize {
+                    return Err(anyhow!(
+                        "Too many decimal places of {}, at most {}",
+                        symbol,
+                        decimals
+                    ));
+                }
+                let parse = |digits: &str| -> Result<Balance> {
+                    if digits.is_empty() {
+                        Ok(0)
+                    } else {
+                        digits
+                            .parse()
+                            .map_err(|err| anyhow!("Invalid digits `{}`: {}", digits, err))
+                    }
+                };
+                let fraction = parse(&format!("{:0<width$}", fraction, width = decimals as usize))?;
+                parse(integer)?
+                    .checked_mul(base(symbol, decimals)?)
+                    .and_then(|units| units.checked_add(fraction))
+                    .ok_or_else(|| anyhow!("Amount of {} overflows", symbol))
+            }
+        }
+    }
+}
+
+/// Returns the symbol and the decimals of asset `asset_id`.
+///
+/// The assets other than PCX are read from `XAssetsRegistrar`.
+pub async fn asset_symbol(client: &ChainXClient, asset_id: AssetId) -> Result<(String, u8)> {
+    if asset_id == PCX_ASSET_ID {
+        return Ok((PCX_SYMBOL.into(), PCX_DECIMALS));
+    }
+    let info = client
+        .asset_info_of(asset_id, None)
+        .await?
+        .ok_or_else(|| anyhow!("Asset #{} is not registered", asset_id))?;
+    Ok((String::from_utf8_lossy(&info.token).into(), info.decimals))
+}
+
+/// Returns one unit of asset `symbol` with `decimals` in the raw units.
+fn base(symbol: &str, decimals: u8) -> Result<Balance> {
+    10u128
+        .checked_pow(decimals as u32)
+        .ok_or_else(|| anyhow!("Too many decimals of {}: {}", symbol, decimals))
+}
+
+/// Formats the raw units with `decimals`, without the trailing zeros of the fraction.
+pub fn format_units(units: Balance, decimals: u8) -> Result<String> {
+    let base = base("the asset", decimals)?;
+    let fraction = format!("{:0width$}", units % base, width = decimals as usize);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        Ok(format!("{}", units / base))
+    } else {
+        Ok(format!("{}.{}", units / base, fraction))
+    }
+}
+
+/// Formats the raw units of asset `symbol` with `decimals`, e.g. `0.001 X-BTC`.
+pub fn format_asset(units: Balance, symbol: &str, decimals: u8) -> Result<String> {
+    Ok(format!("{} {}", format_units(units, decimals)?, symbol))
+}
+
+/// Formats the raw units of PCX, e.g. `12.5 PCX`.
+pub fn format_pcx(units: Balance) -> String {
+    format_asset(units, PCX_SYMBOL, PCX_DECIMALS).expect("Decimals of PCX never overflow; qed")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_amount() {
+        assert_eq!(
+            "1250000000".parse::<Amount>().unwrap().pcx().unwrap(),
+            1_250_000_000
+        );
+        assert_eq!(
+            "12.5PCX".parse::<Amount>().unwrap().pcx().unwrap(),
+            1_250_000_000
+        );
+        assert_eq!(
+            "12.5 pcx".parse::<Amount>().unwrap().pcx().unwrap(),
+            1_250_000_000
+        );
+        assert_eq!("0.00000001PCX".parse::<Amount>().unwrap().pcx().unwrap(), 1);
+        assert_eq!(
+            ".5PCX".parse::<Amount>().unwrap().pcx().unwrap(),
+            50_000_000
+        );
+        assert_eq!("0PCX".parse::<Amount>().unwrap().pcx().unwrap(), 0);
+        assert_eq!(
+            "0.001 X-BTC"
+                .parse::<Amount>()
+                .unwrap()
+                .units("X-BTC", 8)
+                .unwrap(),
+            100_000
+        );
+
+        assert!("12.5".parse::<Amount>().is_err());
+        assert!("PCX".parse::<Amount>().is_err());
+        assert!("1.2.3PCX".parse::<Amount>().is_err());
+        assert!("0.000000001PCX".parse::<Amount>().unwrap().pcx().is_err());
+        assert!("1 X-BTC".parse::<Amount>().unwrap().pcx().is_err());
+        assert!("1X".parse::<Amount>().unwrap().units("X", 39).is_err());
+
+        let amount = Amount::Decimal {
+            integer: "1".into(),
+            fraction: "5x".into(),
+            symbol: PCX_SYMBOL.into(),
+        };
+        let err = amount.pcx().unwrap_err().to_string();
+        assert!(err.starts_with("Invalid digits `5x"), "{}", err);
+    }
+
+    #[test]
+    fn test_format_units() {
+        assert_eq!(format_units(1_250_000_000, 8).unwrap(), "12.5");
+        assert_eq!(format_units(100_000_000, 8).unwrap(), "1");
+        assert_eq!(format_units(105_000_000, 8).unwrap(), "1.05");
+        assert_eq!(format_units(1, 8).unwrap(), "0.00000001");
+        assert_eq!(format_units(0, 8).unwrap(), "0");
+        assert_eq!(format_units(1, 38).unwrap(), format!("0.{:0>38}", 1));
+        assert!(format_units(1, 39).is_err());
+        assert_eq!(format_pcx(1_250_000_000), "12.5 PCX");
+        assert_eq!(format_asset(100_000, "X-BTC", 8).unwrap(), "0.001 X-BTC");
+    }
+}
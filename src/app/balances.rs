@@ -1,11 +1,15 @@
 use anyhow::Result;
 use structopt::StructOpt;
-use subxt::balances::{LocksStoreExt, TransferCallExt, TransferEventExt};
+use subxt::balances::{LocksStoreExt, TransferCall, TransferEvent};
 
 use crate::{
+    amount::{format_pcx, Amount},
+    connection::ConnectionOptions,
+    extrinsic::{Submitter, TxOptions},
+    output::{BalanceLockView, OutputType},
     runtime::{
         primitives::{AccountId, BlockNumber},
-        ChainXSigner,
+        ChainXRuntime, ChainXSigner,
     },
     utils::{block_hash, build_client, parse_account},
 };
@@ -18,9 +22,9 @@ pub enum Balances {
         /// receiver
         #[structopt(index = 1, long, parse(try_from_str = parse_account))]
         dest: AccountId,
-        /// amount
+        /// amount, e.g. `12.5PCX` or the raw units
         #[structopt(index = 2)]
-        value: u128,
+        value: Amount,
     },
     /// Inspect the balances storage items.
     Storage(Storage),
@@ -38,25 +42,49 @@ pub enum Storage {
 }
 
 impl Balances {
-    pub async fn run(self, url: String, signer: ChainXSigner) -> Result<()> {
-        let client = build_client(url).await?;
+    /// Whether the command signs with the signer.
+    pub fn signs(&self) -> bool {
+        !matches!(self, Self::Storage(_))
+    }
+
+    pub async fn run(
+        self,
+        connection: ConnectionOptions,
+        signer: ChainXSigner,
+        options: TxOptions,
+    ) -> Result<()> {
+        let client = build_client(&connection).await?;
+        let output = options.output;
+        let submitter = Submitter::new(client.clone(), &connection, signer, options).await?;
 
         match self {
             Balances::Transfer { dest, value } => {
-                let result = client
-                    .transfer_and_watch(&signer, &dest.into(), value)
-                    .await?;
-                if let Some(event) = result.transfer()? {
-                    println!("Balance transfer success: value: {:?}", event.amount);
-                } else {
-                    println!("Failed to find Balances::Transfer Event");
+                let call = TransferCall::<ChainXRuntime> {
+                    to: &dest.into(),
+                    amount: value.pcx()?,
+                };
+                let outcome = submitter.submit(call).await?;
+                let text = output == OutputType::Text;
+                if let Some(outcome) = outcome.filter(|outcome| text && outcome.is_included()) {
+                    if let Some(event) = outcome.find_event::<TransferEvent<ChainXRuntime>>()? {
+                        println!(
+                            "Balance transfer success: value: {}",
+                            format_pcx(event.amount)
+                        );
+                    } else {
+                        println!("Failed to find Balances::Transfer Event");
+                    }
                 }
             }
             Balances::Storage(storage) => match storage {
                 Storage::Locks { who, block_number } => {
                     let at = block_hash(&client, block_number).await?;
                     let locks = client.locks(&who, at).await?;
-                    println!("{:?}: {:#?}", who, locks);
+                    let json = serde_json::json!({
+                        "who": who,
+                        "locks": locks.iter().map(BalanceLockView::from).collect::<Vec<_>>(),
+                    });
+                    output.print(&json, || println!("{:?}: {:#?}", who, locks))?;
                 }
             },
         }
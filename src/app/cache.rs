@@ -0,0 +1,50 @@
+use anyhow::Result;
+use structopt::StructOpt;
+
+use crate::{
+    cache::StorageCache, connection::ConnectionOptions, output::OutputType,
+    runtime::primitives::Hash,
+};
+
+fn parse_block_hash(hash: &str) -> Result<Hash, String> {
+    hash.trim_start_matches("0x")
+        .parse()
+        .map_err(|err| format!("Invalid block hash `{}`: {:?}", hash, err))
+}
+
+/// Storage cache
+#[derive(Debug, StructOpt)]
+pub enum Cache {
+    /// List the cached storage prefixes and the blocks they are cached at.
+    List,
+    /// Remove the cached storage of a block, or the whole cache.
+    Purge {
+        /// Hash of the block whose cached storage is removed.
+        #[structopt(long, parse(try_from_str = parse_block_hash))]
+        block: Option<Hash>,
+    },
+}
+
+impl Cache {
+    pub async fn run(self, connection: &ConnectionOptions, output: OutputType) -> Result<()> {
+        let cache = StorageCache::new(connection.cache_path()?);
+        match self {
+            Self::List => {
+                let entries = cache.entries()?;
+                output.print(&entries, || {
+                    println!("Storage cache: {}", cache.path().display());
+                    for entry in &entries {
+                        println!("{} {} {} bytes", entry.block, entry.prefix, entry.size);
+                    }
+                })?;
+            }
+            Self::Purge { block } => {
+                let removed = cache.purge(block)?;
+                output.print(&serde_json::json!({ "removed": removed }), || {
+                    println!("Removed {} cached storage prefixes", removed)
+                })?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,286 @@
+use std::{str::FromStr, time::Duration};
+
+use anyhow::{anyhow, Result};
+use codec::Encode;
+use futures::TryStreamExt;
+use sp_runtime::traits::Header as _;
+use structopt::StructOpt;
+use subxt::system::Phase;
+
+use crate::{
+    connection::ConnectionOptions,
+    decoder::{DecodedEvent, DecodedExtrinsic, Decoder},
+    output::OutputType,
+    rpc::{BlockId, ChainBlock, Rpc},
+    runtime::primitives::Hash,
+};
+
+/// Chain
+#[derive(Debug, StructOpt)]
+pub enum Chain {
+    /// Follow the new blocks, print the number, hash, extrinsic count and author of each one.
+    ///
+    /// Requires a websocket url.
+    Follow {
+        /// Follow the finalized blocks instead of the best ones.
+        #[structopt(long)]
+        finalized: bool,
+        /// Reconnect and subscribe again if there is no new block within the given seconds.
+        #[structopt(long, default_value = "60")]
+        stall_timeout: u64,
+    },
+    /// Decode and print the events of a block, grouped by the extrinsic.
+    ///
+    /// Events with the arguments of an unknown type are printed as raw hex.
+    Events {
+        /// Number or hash of the block, default is the latest block.
+        #[structopt(long)]
+        block: Option<BlockId>,
+    },
+    /// Decode and print every extrinsic of a block.
+    Block {
+        /// Number or hash of the block, default is the latest block.
+        #[structopt(index = 1)]
+        block: Option<BlockId>,
+    },
+    /// Decode and print an extrinsic with its events and result.
+    Extrinsic {
+        /// `<block number or hash>-<extrinsic index>`, e.g. `1234-1`.
+        #[structopt(index = 1)]
+        id: ExtrinsicId,
+    },
+}
+
+/// An extrinsic given by the block and the index in the block.
+#[derive(Clone, Copy, Debug)]
+pub struct ExtrinsicId {
+    pub block: BlockId,
+    pub index: u32,
+}
+
+impl FromStr for ExtrinsicId {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.rsplitn(2, '-');
+        match (parts.next(), parts.next()) {
+            (Some(index), Some(block)) => Ok(Self {
+                block: block.parse()?,
+                index: index
+                    .parse()
+                    .map_err(|err| format!("Invalid extrinsic index `{}`: {}", index, err))?,
+            }),
+            _ => Err(format!(
+                "Invalid extrinsic `{}`, expected `<block>-<index>`",
+                s
+            )),
+        }
+    }
+}
+
+/// Fetches the block `hash` and the decoder of its runtime version.
+async fn block_with_decoder(rpc: &Rpc, hash: Hash) -> Result<(ChainBlock, Decoder)> {
+    let block = rpc
+        .block(Some(hash))
+        .await?
+        .ok_or_else(|| anyhow!("Block {:?} not found", hash))?;
+    let decoder = Decoder::new(&rpc.metadata(Some(hash)).await?)?;
+    Ok((block, decoder))
+}
+
+fn print_extrinsic(index: usize, extrinsic: &DecodedExtrinsic) {
+    println!("Extrinsic #{} {:?}", index, extrinsic.hash);
+    if let Some(ref signature) = extrinsic.signature {
+        println!(
+            "    signer {}, nonce {}, tip {}, era {}",
+            signature.signer, signature.nonce, signature.tip.0, signature.era
+        );
+    }
+    println!("    {}", extrinsic.call);
+}
+
+/// Returns the label and the extrinsic index of the phase.
+fn phase_of(phase: &Phase) -> (&'static str, Option<u32>) {
+    match phase {
+        Phase::ApplyExtrinsic(index) => ("applyExtrinsic", Some(*index)),
+        Phase::Finalization => ("finalization", None),
+        Phase::Initialization => ("initialization", None),
+    }
+}
+
+/// Groups the consecutive events of the same phase.
+fn group_by_phase(events: Vec<(Phase, DecodedEvent)>) -> Vec<(Phase, Vec<DecodedEvent>)> {
+    let mut groups: Vec<(Phase, Vec<DecodedEvent>)> = Vec::new();
+    for (phase, event) in events {
+        match groups.last_mut() {
+            Some((last, events)) if *last == phase => events.push(event),
+            _ => groups.push((phase, vec![event])),
+        }
+    }
+    groups
+}
+
+impl Chain {
+    pub async fn run(self, connection: ConnectionOptions, output: OutputType) -> Result<()> {
+        let rpc = Rpc::new(&connection).await?;
+
+        match self {
+            Self::Follow {
+                finalized,
+                stall_timeout,
+            } => {
+                let stall_timeout = Duration::from_secs(stall_timeout);
+                let mut last = None;
+                let mut heads = rpc.subscribe_heads(finalized, last).await?;
+                loop {
+                    let header =
+                        match async_std::future::timeout(stall_timeout, heads.try_next()).await {
+                            Ok(header) => header?,
+                            Err(_) => None,
+                        };
+                    // The subscription ends or stalls silently once the connection is lost.
+                    let header = match header {
+                        Some(header) => header,
+                        None => {
+                            log::warn!("No new block in {:?}, subscribing again", stall_timeout);
+                            rpc.reconnect().await?;
+                            heads = rpc.subscribe_heads(finalized, last).await?;
+                            continue;
+                        }
+                    };
+                    last = Some(header.number);
+                    let hash = header.hash();
+                    let extrinsics = rpc
+                        .block(Some(hash))
+                        .await?
+                        .map(|block| block.block.extrinsics.len())
+                        .unwrap_or_default();
+                    let author = rpc.block_author(&header).await?;
+                    let json = serde_json::json!({
+                        "number": header.number,
+                        "hash": hash,
+                        "extrinsics": extrinsics,
+                        "author": author,
+                    });
+                    output.print_line(&json, || {
+                        let author = author
+                            .as_ref()
+                            .map_or_else(|| "unknown".into(), ToString::to_string);
+                        println!(
+                            "#{} {:?}, {} extrinsics, author {}",
+                            header.number, hash, extrinsics, author
+                        );
+                    })?;
+                }
+            }
+            Self::Events { block } => {
+                let hash = rpc.block_id_hash(block).await?;
+                let decoder = Decoder::new(&rpc.metadata(Some(hash)).await?)?;
+                let events = decoder.decode_events(&rpc.events(Some(hash)).await?)?;
+                let groups = group_by_phase(events);
+
+                let json_groups = groups
+                    .iter()
+                    .map(|(phase, events)| {
+                        let (phase, extrinsic) = phase_of(phase);
+                        serde_json::json!({
+                            "phase": phase,
+                            "extrinsic": extrinsic,
+                            "events": events,
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                let json = serde_json::json!({ "block": hash, "groups": json_groups });
+                output.print(&json, || {
+                    println!("Block {:?}", hash);
+                    for (phase, events) in &groups {
+                        match phase_of(phase) {
+                            (_, Some(index)) => println!("Extrinsic #{}:", index),
+                            (phase, None) => println!("{}:", phase),
+                        }
+                        for event in events {
+                            println!("    {}", event);
+                        }
+                    }
+                })?;
+            }
+            Self::Block { block } => {
+                let hash = rpc.block_id_hash(block).await?;
+                let (block, decoder) = block_with_decoder(&rpc, hash).await?;
+                let extrinsics = block
+                    .block
+                    .extrinsics
+                    .iter()
+                    .map(|extrinsic| decoder.decode_extrinsic(&extrinsic.encode()))
+                    .collect::<Result<Vec<_>>>()?;
+
+                let header = &block.block.header;
+                let json = serde_json::json!({
+                    "number": header.number,
+                    "hash": hash,
+                    "parentHash": header.parent_hash,
+                    "extrinsics": extrinsics,
+                });
+                output.print(&json, || {
+                    println!("Block #{} {:?}", header.number, hash);
+                    println!("Parent {:?}", header.parent_hash);
+                    for (index, extrinsic) in extrinsics.iter().enumerate() {
+                        print_extrinsic(index, extrinsic);
+                    }
+                })?;
+            }
+            Self::Extrinsic { id } => {
+                let hash = rpc.block_id_hash(Some(id.block)).await?;
+                let (block, decoder) = block_with_decoder(&rpc, hash).await?;
+                let extrinsic = block
+                    .block
+                    .extrinsics
+                    .get(id.index as usize)
+                    .ok_or_else(|| anyhow!("Block {:?} has no extrinsic #{}", hash, id.index))?;
+                let extrinsic = decoder.decode_extrinsic(&extrinsic.encode())?;
+
+                let events = decoder
+                    .decode_events(&rpc.events(Some(hash)).await?)?
+                    .into_iter()
+                    .filter(|(phase, _)| *phase == Phase::ApplyExtrinsic(id.index))
+                    .map(|(_, event)| event)
+                    .collect::<Vec<_>>();
+                let failed = events
+                    .iter()
+                    .find(|event| event.pallet == "System" && event.event == "ExtrinsicFailed");
+                let success = events
+                    .iter()
+                    .any(|event| event.pallet == "System" && event.event == "ExtrinsicSuccess");
+                let result = match (failed, success) {
+                    (Some(failed), _) => serde_json::json!({ "failed": failed.args.first() }),
+                    (None, true) => serde_json::json!("success"),
+                    (None, false) => serde_json::json!("unknown"),
+                };
+
+                let json = serde_json::json!({
+                    "block": hash,
+                    "number": block.block.header.number,
+                    "index": id.index,
+                    "extrinsic": extrinsic,
+                    "events": events,
+                    "result": result,
+                });
+                output.print(&json, || {
+                    println!("Block #{} {:?}", block.block.header.number, hash);
+                    print_extrinsic(id.index as usize, &extrinsic);
+                    println!("Events:");
+                    for event in &events {
+                        println!("    {}", event);
+                    }
+                    match failed {
+                        Some(failed) => println!("Failed: {}", failed),
+                        None if success => println!("Succeeded"),
+                        None => println!("Result unknown, the events can't be decoded"),
+                    }
+                })?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,335 @@
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use sp_core::{
+    crypto::{Ss58AddressFormat, Ss58Codec},
+    ecdsa, ed25519, sr25519, Pair,
+};
+use sp_runtime::{traits::IdentifyAccount, MultiSigner};
+use structopt::StructOpt;
+
+use crate::{
+    keystore::{read_password, KeyFile, Keystore, KEYSTORE_PASSWORD_ENV},
+    output::OutputType,
+    runtime::{primitives::AccountId, CryptoScheme},
+    utils::parse_account,
+};
+
+/// Key
+#[derive(Debug, StructOpt)]
+pub enum Key {
+    /// Add an account of `--scheme` to the keystore.
+    ///
+    /// The secret URI is read from the terminal unless `--suri-file` is specified.
+    Add {
+        /// Name of the account.
+        #[structopt(index = 1)]
+        name: String,
+        /// File that contains the secret URI.
+        #[structopt(long, parse(from_os_str))]
+        suri_file: Option<PathBuf>,
+    },
+    /// List all the accounts in the keystore.
+    List,
+    /// Remove an account from the keystore.
+    Remove {
+        /// Name of the account.
+        #[structopt(index = 1)]
+        name: String,
+    },
+    /// Print the secret URI of an account in the keystore.
+    Export {
+        /// Name of the account.
+        #[structopt(index = 1)]
+        name: String,
+    },
+    /// Generate a new random key of `--scheme`.
+    Generate,
+    /// Inspect a SS58 address, a hex public key or a secret URI of `--scheme`.
+    Inspect {
+        /// Secret URI, SS58 address or `0x`-prefixed public key.
+        #[structopt(index = 1)]
+        input: String,
+        /// Treat the `0x`-prefixed hex as a secret seed instead of a public key.
+        #[structopt(long)]
+        secret: bool,
+    },
+    /// Convert an address or public key to the SS58 address of another network.
+    Convert {
+        /// SS58 address or `0x`-prefixed public key.
+        #[structopt(index = 1, parse(try_from_str = parse_account))]
+        address: AccountId,
+        /// Ss58 Address version of the target network, 44 for ChainX, 42 for Substrate.
+        #[structopt(long)]
+        to_prefix: Ss58AddressFormat,
+    },
+}
+
+/// Information of a key, the secret parts are only available for the secret URI.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct KeyInfo {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    scheme: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    secret_phrase: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    secret_seed: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    public_key: Option<String>,
+    account_id: String,
+    ss58_address: String,
+}
+
+impl KeyInfo {
+    fn from_account_id(account_id: &AccountId, ss58_prefix: Option<Ss58AddressFormat>) -> Self {
+        Self {
+            account_id: format!("0x{}", hex::encode(account_id)),
+            ss58_address: match ss58_prefix {
+                Some(prefix) => account_id.to_ss58check_with_version(prefix),
+                None => account_id.to_ss58check(),
+            },
+            ..Default::default()
+        }
+    }
+
+    fn from_pair<P>(scheme: CryptoScheme, pair: P, seed: Option<P::Seed>) -> Self
+    where
+        P: Pair,
+        P::Seed: AsRef<[u8]>,
+        MultiSigner: From<P::Public>,
+    {
+        let public = pair.public();
+        let account_id = MultiSigner::from(public.clone()).into_account();
+        Self {
+            scheme: Some(scheme.to_string()),
+            secret_seed: seed.map(|seed| format!("0x{}", hex::encode(seed))),
+            public_key: Some(format!("0x{}", hex::encode(public))),
+            ..Self::from_account_id(&account_id, None)
+        }
+    }
+
+    fn print(&self, output: OutputType) -> Result<()> {
+        output.print(self, || {
+            if let Some(ref scheme) = self.scheme {
+                println!("Scheme:           {}", scheme);
+            }
+            if let Some(ref secret_phrase) = self.secret_phrase {
+                println!("Secret phrase:    {}", secret_phrase);
+            }
+            if let Some(ref secret_seed) = self.secret_seed {
+                println!("Secret seed:      {}", secret_seed);
+            }
+            if let Some(ref public_key) = self.public_key {
+                println!("Public key (hex): {}", public_key);
+            }
+            println!("Account ID:       {}", self.account_id);
+            println!("SS58 Address:     {}", self.ss58_address);
+        })
+    }
+}
+
+/// An account in the keystore, without the encrypted secret.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct KeyEntry {
+    name: String,
+    scheme: String,
+    account_id: AccountId,
+}
+
+impl From<&KeyFile> for KeyEntry {
+    fn from(key_file: &KeyFile) -> Self {
+        Self {
+            name: key_file.name.clone(),
+            scheme: key_file.scheme.to_string(),
+            account_id: key_file.account_id.clone(),
+        }
+    }
+}
+
+fn generate<P>(scheme: CryptoScheme) -> KeyInfo
+where
+    P: Pair,
+    P::Seed: AsRef<[u8]>,
+    MultiSigner: From<P::Public>,
+{
+    let (pair, phrase, seed) = P::generate_with_phrase(None);
+    KeyInfo {
+        secret_phrase: Some(phrase),
+        ..KeyInfo::from_pair(scheme, pair, Some(seed))
+    }
+}
+
+fn inspect_uri<P>(scheme: CryptoScheme, uri: &str) -> Option<KeyInfo>
+where
+    P: Pair,
+    P::Seed: AsRef<[u8]>,
+    MultiSigner: From<P::Public>,
+{
+    P::from_string_with_seed(uri, None)
+        .ok()
+        .map(|(pair, seed)| KeyInfo::from_pair(scheme, pair, seed))
+}
+
+/// Inspects the SS58 address, the `0x`-prefixed public key, or the secret URI.
+///
+/// A 32-byte hex is also a valid secret seed, it's only treated as a secret if `secret`.
+fn inspect(scheme: CryptoScheme, input: &str, secret: bool) -> Result<KeyInfo> {
+    if let Ok(account_id) = AccountId::from_ss58check(input) {
+        return Ok(KeyInfo::from_account_id(&account_id, None));
+    }
+    let is_hex = input.starts_with("0x") && hex::decode(&input[2..]).is_ok();
+    let secret_info = if is_hex && !secret {
+        None
+    } else {
+        match scheme {
+            CryptoScheme::Sr25519 => inspect_uri::<sr25519::Pair>(scheme, input),
+            CryptoScheme::Ed25519 => inspect_uri::<ed25519::Pair>(scheme, input),
+            CryptoScheme::Ecdsa => inspect_uri::<ecdsa::Pair>(scheme, input),
+        }
+    };
+    match secret_info {
+        Some(key_info) => Ok(key_info),
+        None if secret => Err(anyhow!("Invalid secret seed of {}", scheme)),
+        None => Ok(KeyInfo::from_account_id(&parse_account(input)?, None)),
+    }
+}
+
+fn read_suri(suri_file: Option<PathBuf>) -> Result<String> {
+    let suri = if let Some(path) = suri_file {
+        std::fs::read_to_string(path)?
+    } else {
+        rpassword::read_password_from_tty(Some("Secret URI: "))?
+    };
+    let suri = suri.trim();
+    if suri.is_empty() {
+        return Err(anyhow!("Secret URI can not be empty"));
+    }
+    Ok(suri.into())
+}
+
+impl Key {
+    pub async fn run(
+        self,
+        keystore_path: PathBuf,
+        scheme: CryptoScheme,
+        output: OutputType,
+    ) -> Result<()> {
+        let keystore = || Keystore::open(&keystore_path);
+
+        match self {
+            Self::Add { name, suri_file } => {
+                let suri = read_suri(suri_file)?;
+                let password = read_password("Password: ")?;
+                if std::env::var(KEYSTORE_PASSWORD_ENV).is_err()
+                    && read_password("Repeat password: ")? != password
+                {
+                    return Err(anyhow!("Passwords do not match"));
+                }
+                let key_file = keystore()?.add(&name, scheme, &suri, &password)?;
+                output.print(&KeyEntry::from(&key_file), || {
+                    println!(
+                        "Added {} account `{}`: {}",
+                        key_file.scheme, key_file.name, key_file.account_id
+                    )
+                })?;
+            }
+            Self::List => {
+                let key_files = keystore()?.list()?;
+                let entries = key_files.iter().map(KeyEntry::from).collect::<Vec<_>>();
+                output.print(&entries, || {
+                    for key_file in &key_files {
+                        println!(
+                            "{} ({}): {}",
+                            key_file.name, key_file.scheme, key_file.account_id
+                        );
+                    }
+                })?;
+            }
+            Self::Remove { name } => {
+                keystore()?.remove(&name)?;
+                let json = serde_json::json!({ "removed": name });
+                output.print(&json, || println!("Removed account `{}`", name))?;
+            }
+            Self::Export { name } => {
+                let password = read_password(&format!("Password of `{}`: ", name))?;
+                let suri = keystore()?.export(&name, &password)?;
+                let json = serde_json::json!({ "name": name, "suri": suri });
+                output.print(&json, || println!("{}", suri))?;
+            }
+            Self::Generate => {
+                let key_info = match scheme {
+                    CryptoScheme::Sr25519 => generate::<sr25519::Pair>(scheme),
+                    CryptoScheme::Ed25519 => generate::<ed25519::Pair>(scheme),
+                    CryptoScheme::Ecdsa => generate::<ecdsa::Pair>(scheme),
+                };
+                key_info.print(output)?;
+            }
+            Self::Inspect { input, secret } => {
+                inspect(scheme, &input, secret)?.print(output)?;
+            }
+            Self::Convert { address, to_prefix } => {
+                KeyInfo::from_account_id(&address, Some(to_prefix)).print(output)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALICE: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+    const ALICE_PUBLIC: &str = "0xd43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d";
+
+    #[test]
+    fn test_generate() {
+        let scheme = CryptoScheme::Sr25519;
+        let key_info = generate::<sr25519::Pair>(scheme);
+        let phrase = key_info.secret_phrase.clone().unwrap();
+        let inspected = inspect(scheme, &phrase, false).unwrap();
+        assert_eq!(inspected.account_id, key_info.account_id);
+        assert_eq!(inspected.secret_seed, key_info.secret_seed);
+    }
+
+    #[test]
+    fn test_inspect() {
+        let scheme = CryptoScheme::Sr25519;
+        let alice = inspect(scheme, "//Alice", false).unwrap();
+        assert_eq!(alice.public_key.as_deref(), Some(ALICE_PUBLIC));
+        assert_eq!(alice.account_id, ALICE_PUBLIC);
+
+        let address = inspect(scheme, ALICE, false).unwrap();
+        assert!(address.secret_seed.is_none());
+        assert_eq!(address.account_id, ALICE_PUBLIC);
+
+        // A public key is never mistaken for a secret seed.
+        let public = inspect(scheme, ALICE_PUBLIC, false).unwrap();
+        assert!(public.secret_seed.is_none());
+        assert_eq!(public.account_id, ALICE_PUBLIC);
+
+        let seed = inspect(scheme, ALICE_PUBLIC, true).unwrap();
+        assert_eq!(seed.secret_seed.as_deref(), Some(ALICE_PUBLIC));
+        assert_ne!(seed.account_id, ALICE_PUBLIC);
+
+        assert!(inspect(scheme, "0x1234", true).is_err());
+        assert!(inspect(scheme, "0x1234", false).is_err());
+    }
+
+    #[test]
+    fn test_convert() {
+        let alice = parse_account(ALICE).unwrap();
+        let substrate = KeyInfo::from_account_id(&alice, Some(Ss58AddressFormat::SubstrateAccount));
+        assert_eq!(substrate.ss58_address, ALICE);
+        let chainx = KeyInfo::from_account_id(&alice, Some(Ss58AddressFormat::ChainXAccount));
+        assert_ne!(chainx.ss58_address, ALICE);
+        assert_eq!(
+            AccountId::from_ss58check_with_version(&chainx.ss58_address).ok(),
+            Some((alice, Ss58AddressFormat::ChainXAccount))
+        );
+    }
+}
@@ -0,0 +1,186 @@
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+use codec::{DecodeAll, Encode};
+use sp_core::{ecdsa, ed25519, sr25519};
+use sp_runtime::traits::Verify;
+use structopt::StructOpt;
+use subxt::Signer;
+
+use crate::{
+    output::OutputType,
+    runtime::{
+        primitives::{AccountId, Signature},
+        ChainXSigner,
+    },
+    utils::parse_account,
+};
+
+const BYTES_PREFIX: &[u8] = b"<Bytes>";
+const BYTES_SUFFIX: &[u8] = b"</Bytes>";
+
+/// Wraps the message with `<Bytes>...</Bytes>` as polkadot-js does.
+fn wrap_bytes(message: &[u8]) -> Vec<u8> {
+    [BYTES_PREFIX, message, BYTES_SUFFIX].concat()
+}
+
+#[derive(Debug, StructOpt)]
+pub struct Message {
+    /// The message, signed as the raw bytes of the string unless `--hex` is specified.
+    #[structopt(long, required_unless = "file")]
+    message: Option<String>,
+    /// Treat the message as a `0x`-prefixed hex string.
+    #[structopt(long)]
+    hex: bool,
+    /// Read the message from the file.
+    #[structopt(long, parse(from_os_str), conflicts_with = "message")]
+    file: Option<PathBuf>,
+}
+
+impl Message {
+    fn read(&self) -> Result<Vec<u8>> {
+        let message = if let Some(ref path) = self.file {
+            std::fs::read(path)?
+        } else if let Some(ref message) = self.message {
+            message.as_bytes().to_vec()
+        } else {
+            return Err(anyhow!("Either --message or --file is required"));
+        };
+        if self.hex {
+            let message = String::from_utf8(message)?;
+            let message = message.trim();
+            Ok(hex::decode(message.strip_prefix("0x").unwrap_or(message))?)
+        } else {
+            Ok(message)
+        }
+    }
+}
+
+/// Sign a message with the signer.
+#[derive(Debug, StructOpt)]
+pub struct SignMessage {
+    #[structopt(flatten)]
+    message: Message,
+    /// Wrap the message with `<Bytes>...</Bytes>` before signing, as polkadot-js does.
+    #[structopt(long)]
+    wrap: bool,
+    /// Output the raw signature without the `MultiSignature` scheme prefix.
+    #[structopt(long)]
+    raw: bool,
+}
+
+impl SignMessage {
+    pub async fn run(self, signer: ChainXSigner, output: OutputType) -> Result<()> {
+        let message = self.message.read()?;
+        let message = if self.wrap {
+            wrap_bytes(&message)
+        } else {
+            message
+        };
+
+        let signature = signer.sign_message(&message);
+        let signature = if self.raw {
+            match signature {
+                Signature::Sr25519(ref signature) => signature.as_ref().to_vec(),
+                Signature::Ed25519(ref signature) => signature.as_ref().to_vec(),
+                Signature::Ecdsa(ref signature) => signature.as_ref().to_vec(),
+            }
+        } else {
+            signature.encode()
+        };
+
+        let signature = format!("0x{}", hex::encode(signature));
+        let json = serde_json::json!({
+            "signer": signer.account_id(),
+            "scheme": signer.scheme().to_string(),
+            "signature": signature,
+        });
+        output.print(&json, || {
+            println!("Signer:    {}", signer.account_id());
+            println!("Scheme:    {}", signer.scheme());
+            println!("Signature: {}", signature);
+        })
+    }
+}
+
+/// Verify a message signature against an account.
+#[derive(Debug, StructOpt)]
+pub struct VerifyMessage {
+    /// The account that signed the message.
+    #[structopt(index = 1, long, parse(try_from_str = parse_account))]
+    who: AccountId,
+    /// The `0x`-prefixed hex signature, either the raw one or the SCALE encoded `MultiSignature`.
+    #[structopt(index = 2, long)]
+    signature: String,
+    #[structopt(flatten)]
+    message: Message,
+}
+
+/// Returns all the possible `MultiSignature`s of the signature bytes.
+fn signature_candidates(signature: &[u8]) -> Vec<Signature> {
+    let mut candidates = Vec::new();
+    if let Ok(signature) = Signature::decode_all(signature) {
+        candidates.push(signature);
+    }
+    match signature.len() {
+        64 => {
+            let mut raw = [0u8; 64];
+            raw.copy_from_slice(signature);
+            candidates.push(sr25519::Signature::from_raw(raw).into());
+            candidates.push(ed25519::Signature::from_raw(raw).into());
+        }
+        65 => {
+            let mut raw = [0u8; 65];
+            raw.copy_from_slice(signature);
+            candidates.push(ecdsa::Signature::from_raw(raw).into());
+        }
+        _ => {}
+    }
+    candidates
+}
+
+fn scheme_of(signature: &Signature) -> &'static str {
+    match signature {
+        Signature::Sr25519(_) => "Sr25519",
+        Signature::Ed25519(_) => "Ed25519",
+        Signature::Ecdsa(_) => "Ecdsa",
+    }
+}
+
+impl VerifyMessage {
+    pub async fn run(self, output: OutputType) -> Result<()> {
+        let message = self.message.read()?;
+        let signature = hex::decode(self.signature.trim_start_matches("0x"))?;
+
+        let candidates = signature_candidates(&signature);
+        if candidates.is_empty() {
+            return Err(anyhow!("Invalid signature length: {}", signature.len()));
+        }
+
+        let wrapped = wrap_bytes(&message);
+        for candidate in candidates {
+            let is_wrapped = if candidate.verify(&message[..], &self.who) {
+                false
+            } else if candidate.verify(&wrapped[..], &self.who) {
+                true
+            } else {
+                continue;
+            };
+            let json = serde_json::json!({
+                "who": self.who,
+                "scheme": scheme_of(&candidate),
+                "wrapped": is_wrapped,
+            });
+            return output.print(&json, || {
+                println!(
+                    "Valid {} signature of {}{}",
+                    scheme_of(&candidate),
+                    self.who,
+                    if is_wrapped { " (<Bytes> wrapped)" } else { "" }
+                )
+            });
+        }
+
+        Err(anyhow!("Invalid signature of {}", self.who))
+    }
+}
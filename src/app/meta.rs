@@ -5,6 +5,8 @@ use frame_metadata::{
     RuntimeMetadata, RuntimeMetadataPrefixed,
 };
 
+use crate::{connection::ConnectionOptions, rpc::Rpc};
+
 /// Metadata
 #[derive(Debug, StructOpt)]
 pub enum Meta {
@@ -13,19 +15,17 @@ pub enum Meta {
 
 #[derive(Debug, StructOpt)]
 pub struct GetOpt {
-    /// the url of the substrate node to query for metadata
-    #[structopt(index = 1, default_value = "http://localhost:8087")]
-    url: String,
     /// the name of a pallet to display metadata for, otherwise displays all
-    #[structopt(index = 2, short = "p")]
+    #[structopt(index = 1, short = "p")]
     pallet: Option<String>,
 }
 
 impl Meta {
-    pub async fn run(self) -> Result<()> {
+    pub async fn run(self, connection: ConnectionOptions) -> Result<()> {
         match self {
             Meta::Get(get_opt) => {
-                let metadata = Self::fetch_metadata(&get_opt.url)?;
+                let rpc = Rpc::new(&connection).await?;
+                let metadata = Self::fetch_metadata(&rpc).await?;
                 Self::display_metadata(metadata, get_opt.pallet)?;
             }
         }
@@ -33,22 +33,11 @@ impl Meta {
         Ok(())
     }
 
-    fn fetch_metadata(url: &str) -> Result<RuntimeMetadataPrefixed> {
-        let resp = ureq::post(url)
-            .set("Content-Type", "application/json")
-            .send_json(ureq::json!({
-                "jsonrpc": "2.0",
-                "method": "state_getMetadata",
-                "id": 1
-            }))
+    async fn fetch_metadata(rpc: &Rpc) -> Result<RuntimeMetadataPrefixed> {
+        let bytes = rpc
+            .metadata(None)
+            .await
             .context("error fetching metadata from the substrate node")?;
-
-        let json: serde_json::Value = resp.into_json()?;
-        let hex_data = json["result"]
-            .as_str()
-            .ok_or(anyhow!("metadata result field should be a string"))?;
-
-        let bytes = hex::decode(hex_data.trim_start_matches("0x"))?;
         let decoded = scale::Decode::decode(&mut &bytes[..])?;
         Ok(decoded)
     }
@@ -1,29 +1,60 @@
 pub mod balances;
+pub mod cache;
+pub mod chain;
+pub mod key;
+pub mod message;
+pub mod meta;
 pub mod session;
+pub mod state;
 pub mod sudo;
 pub mod system;
+pub mod tx;
 pub mod xassets;
 pub mod xmining_asset;
 pub mod xstaking;
-pub mod meta;
 
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::{
+    amount::Amount,
+    connection::ConnectionOptions,
+    extrinsic::{TxOptions, WaitFor},
+    keystore::{read_password, Keystore},
+    output::OutputType,
+    runtime::{primitives::Index, ChainXSigner, CryptoScheme},
+};
 use anyhow::{anyhow, Result};
-use sp_core::Pair;
 use sp_keyring::AccountKeyring;
 use structopt::{clap::arg_enum, StructOpt};
-use subxt::PairSigner;
-use crate::runtime::ChainXSigner;
 
 #[derive(StructOpt, Debug)]
 pub enum Cmd {
     Balances(balances::Balances),
+    /// List or purge the local storage cache enabled by `--cache`.
+    Cache(cache::Cache),
+    /// Follow and inspect the blocks.
+    Chain(chain::Chain),
+    /// Generate, inspect the keys and manage the accounts in the local keystore.
+    Key(key::Key),
     Session(session::Session),
+    /// Dump the storage to a state file for the offline analysis.
+    State(state::State),
 
     #[structopt(name = "meta", about = "An tool for inspecting substrate metadata")]
     Meta(meta::Meta),
 
+    /// Sign a message with the signer.
+    SignMessage(message::SignMessage),
+    /// Verify a message signature against an account.
+    VerifyMessage(message::VerifyMessage),
+
     Sudo(sudo::Sudo),
     System(system::System),
+    /// Build, sign and submit the transaction separately for the air-gapped signer.
+    Tx(tx::Tx),
 
     #[structopt(name = "xassets")]
     XAssets(xassets::XAssets),
@@ -65,6 +96,23 @@ impl Into<AccountKeyring> for BuiltinAccounts {
     }
 }
 
+impl Cmd {
+    /// Whether the command signs with the signer.
+    fn signs(&self) -> bool {
+        match self {
+            Self::Balances(balances) => balances.signs(),
+            Self::Session(session) => session.signs(),
+            Self::SignMessage(_) | Self::Sudo(_) => true,
+            Self::System(system) => system.signs(),
+            Self::Tx(tx) => tx.signs(),
+            Self::XAssets(xassets) => xassets.signs(),
+            Self::XMiningAsset(xmining_asset) => xmining_asset.signs(),
+            Self::XStaking(xstaking) => xstaking.signs(),
+            _ => false,
+        }
+    }
+}
+
 #[derive(StructOpt, Debug)]
 #[structopt(name = "chainx-cli", author, about, no_version)]
 pub struct App {
@@ -72,6 +120,15 @@ pub struct App {
     #[structopt(long, possible_values = &BuiltinAccounts::variants(), case_insensitive = true)]
     pub signer: Option<BuiltinAccounts>,
 
+    /// Cryptography scheme of the signer.
+    #[structopt(
+        long,
+        default_value = "sr25519",
+        possible_values = &CryptoScheme::variants(),
+        case_insensitive = true
+    )]
+    pub scheme: CryptoScheme,
+
     /// A Key URI used as a signer.
     ///
     /// Maybe a secret seed, secret URI(with derivation paths and password), SS58 or public URI.
@@ -79,9 +136,28 @@ pub struct App {
     #[structopt(long)]
     pub uri: Option<String>,
 
-    /// The websocket url of ChainX node.
-    #[structopt(long, default_value = "ws://127.0.0.1:8087")]
-    pub url: String,
+    /// The password of the secret URI, overrides the `///password` part of `--uri` if any.
+    #[structopt(long, conflicts_with = "password-file")]
+    pub password: Option<String>,
+
+    /// File that contains the password of the secret URI.
+    #[structopt(long, parse(from_os_str))]
+    pub password_file: Option<PathBuf>,
+
+    /// Name of the account in the keystore used as a signer.
+    ///
+    /// The keystore password is read from the terminal or the environment variable KEYSTORE_PASSWORD.
+    #[structopt(long, conflicts_with_all = &["signer", "uri"])]
+    pub account: Option<String>,
+
+    /// Directory of the keystore.
+    ///
+    /// Default is `<data_dir>/chainx-cli/keystore`.
+    #[structopt(long, parse(from_os_str))]
+    pub keystore: Option<PathBuf>,
+
+    #[structopt(flatten)]
+    pub connection: ConnectionOptions,
 
     /// Ss58 Address version of the network.
     ///
@@ -89,14 +165,58 @@ pub struct App {
     #[structopt(long, default_value = "44")]
     pub ss58_prefix: sp_core::crypto::Ss58AddressFormat,
 
+    /// Dry run the extrinsic and estimate the fee instead of submitting it.
+    #[structopt(long)]
+    pub dry_run: bool,
+
+    /// When the submission of an extrinsic is considered done.
+    ///
+    /// `none` returns the extrinsic hash immediately, `in-block` returns once it's included
+    /// in a block, `finalized` returns once the block is finalized.
+    #[structopt(long, default_value = "finalized", possible_values = &WaitFor::VARIANTS)]
+    pub wait: WaitFor,
+
+    /// Give up waiting for the extrinsic after the given seconds.
+    #[structopt(long)]
+    pub timeout: Option<u64>,
+
+    /// Nonce of the extrinsic, e.g. to replace a stuck one.
+    ///
+    /// Default is the next index of the signer. The following extrinsics submitted by the
+    /// same command use the increasing nonces.
+    #[structopt(long)]
+    pub nonce: Option<Index>,
+
+    /// Tip for the block author, to increase the priority of the extrinsic.
+    ///
+    /// e.g. `0.1PCX` or the raw units.
+    #[structopt(long, default_value = "0")]
+    pub tip: Amount,
+
+    /// Make the extrinsic mortal, valid for the given number of blocks since the finalized one.
+    ///
+    /// The period is rounded up to a power of two between 4 and 65536.
+    #[structopt(long)]
+    pub mortal_period: Option<u64>,
+
+    /// Print the result as text or a single JSON document.
+    ///
+    /// Balances are strings and accounts are SS58 addresses of `--ss58-prefix` in JSON.
+    #[structopt(
+        long,
+        default_value = "text",
+        possible_values = &OutputType::variants(),
+        case_insensitive = true
+    )]
+    pub output: OutputType,
+
     #[structopt(subcommand)]
     pub command: Cmd,
 }
 
-fn as_sr25519_signer(uri: &str) -> Result<ChainXSigner> {
-    sp_core::sr25519::Pair::from_phrase(&uri, None)
-    .map(|(pair, _seed)| PairSigner::new(pair))
-    .map_err(|err| anyhow!("Failed to generate sr25519 Pair from uri(): {:?}", err))
+fn as_signer(scheme: CryptoScheme, uri: &str, password: Option<&str>) -> Result<ChainXSigner> {
+    ChainXSigner::from_uri(scheme, uri, password)
+        .map_err(|err| anyhow!("Failed to generate {} Pair from uri: {:?}", scheme, err))
 }
 
 impl App {
@@ -107,30 +227,65 @@ impl App {
     pub async fn run(self) -> Result<()> {
         sp_core::crypto::set_default_ss58_version(self.ss58_prefix);
 
-        let signer = if let Some(ref uri) = self.get_uri() {
-            as_sr25519_signer(uri)?
+        let keystore_path = self.keystore_path()?;
+
+        // The read-only commands never sign, don't prompt for the keystore password for them.
+        let signer = if self.command.signs() {
+            self.get_signer(&keystore_path)?
         } else {
-            self.builtin_signer()
+            self.builtin_signer()?
+        };
+
+        let options = TxOptions {
+            dry_run: self.dry_run,
+            wait: self.wait,
+            timeout: self.timeout.map(Duration::from_secs),
+            nonce: self.nonce,
+            tip: self.tip.pcx()?,
+            mortal_period: self.mortal_period,
+            output: self.output,
         };
 
         match self.command {
-            Cmd::Balances(balances) => balances.run(self.url, signer).await?,
-            Cmd::Session(session) => session.run(self.url, signer).await?,
-            Cmd::Meta(meta) => meta.run().await?,
-            Cmd::Sudo(sudo) => sudo.run(self.url, signer).await?,
-            Cmd::System(system) => system.run(self.url, signer).await?,
-            Cmd::XAssets(xassets) => xassets.run(self.url, signer).await?,
-            Cmd::XMiningAsset(xmining_asset) => xmining_asset.run(self.url, signer).await?,
-            Cmd::XStaking(xstaking) => xstaking.run(self.url, signer).await?,
+            Cmd::Balances(balances) => balances.run(self.connection, signer, options).await?,
+            Cmd::Cache(cache) => cache.run(&self.connection, self.output).await?,
+            Cmd::Chain(chain) => chain.run(self.connection, self.output).await?,
+            Cmd::Key(key) => key.run(keystore_path, self.scheme, self.output).await?,
+            Cmd::Session(session) => session.run(self.connection, signer, self.output).await?,
+            Cmd::State(state) => state.run(self.connection, self.output).await?,
+            Cmd::Meta(meta) => meta.run(self.connection).await?,
+            Cmd::SignMessage(sign_message) => sign_message.run(signer, self.output).await?,
+            Cmd::VerifyMessage(verify_message) => verify_message.run(self.output).await?,
+            Cmd::Sudo(sudo) => sudo.run(self.connection, signer, options).await?,
+            Cmd::System(system) => system.run(self.connection, signer, options).await?,
+            Cmd::Tx(tx) => tx.run(self.connection, signer, options).await?,
+            Cmd::XAssets(xassets) => xassets.run(self.connection, signer, options).await?,
+            Cmd::XMiningAsset(xmining_asset) => {
+                xmining_asset.run(self.connection, signer, options).await?
+            }
+            Cmd::XStaking(xstaking) => xstaking.run(self.connection, signer, options).await?,
             #[cfg(feature = "sc-cli")]
             Cmd::InspectKey => {
                 if let Some(ref uri) = self.get_uri() {
-                    sc_cli::utils::print_from_uri::<sp_core::sr25519::Pair>(
-                        uri,
-                        None,
-                        Some(self.ss58_prefix),
-                        sc_cli::OutputType::Text,
-                    );
+                    let network = Some(self.ss58_prefix);
+                    let output = sc_cli::OutputType::Text;
+                    match self.scheme {
+                        CryptoScheme::Sr25519 => {
+                            sc_cli::utils::print_from_uri::<sp_core::sr25519::Pair>(
+                                uri, None, network, output,
+                            )
+                        }
+                        CryptoScheme::Ed25519 => {
+                            sc_cli::utils::print_from_uri::<sp_core::ed25519::Pair>(
+                                uri, None, network, output,
+                            )
+                        }
+                        CryptoScheme::Ecdsa => {
+                            sc_cli::utils::print_from_uri::<sp_core::ecdsa::Pair>(
+                                uri, None, network, output,
+                            )
+                        }
+                    }
                 }
             }
         }
@@ -148,9 +303,44 @@ impl App {
         }
     }
 
-    fn builtin_signer(&self) -> ChainXSigner {
+    fn keystore_path(&self) -> Result<PathBuf> {
+        if let Some(ref path) = self.keystore {
+            Ok(path.clone())
+        } else {
+            Keystore::default_path()
+        }
+    }
+
+    fn get_password(&self) -> Result<Option<String>> {
+        if let Some(ref password) = self.password {
+            Ok(Some(password.clone()))
+        } else if let Some(ref path) = self.password_file {
+            let password = std::fs::read_to_string(path)?;
+            Ok(Some(password.trim_end_matches(&['\r', '\n'][..]).into()))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn get_signer(&self, keystore_path: &Path) -> Result<ChainXSigner> {
+        if let Some(ref name) = self.account {
+            let password = read_password(&format!("Password of `{}`: ", name))?;
+            Keystore::open(keystore_path)?.signer(name, &password)
+        } else if let Some(ref uri) = self.get_uri() {
+            as_signer(self.scheme, uri, self.get_password()?.as_deref())
+        } else {
+            self.builtin_signer()
+        }
+    }
+
+    fn builtin_signer(&self) -> Result<ChainXSigner> {
         let signer = self.signer.clone().unwrap_or(BuiltinAccounts::Alice);
-        let signer: AccountKeyring = signer.into();
-        PairSigner::new(signer.pair())
+        match self.scheme {
+            CryptoScheme::Sr25519 => {
+                let signer: AccountKeyring = signer.into();
+                Ok(signer.pair().into())
+            }
+            scheme => as_signer(scheme, &format!("//{}", signer), None),
+        }
     }
 }
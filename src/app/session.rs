@@ -2,6 +2,8 @@ use anyhow::Result;
 use subxt::session::ValidatorsStoreExt;
 
 use crate::{
+    connection::ConnectionOptions,
+    output::OutputType,
     runtime::{primitives::BlockNumber, ChainXSigner},
     utils::{block_hash, build_client},
 };
@@ -20,13 +22,24 @@ pub enum Session {
 }
 
 impl Session {
-    pub async fn run(self, url: String, _signer: ChainXSigner) -> Result<()> {
-        let client = build_client(url).await?;
+    /// Whether the command signs with the signer.
+    pub fn signs(&self) -> bool {
+        !matches!(self, Self::Validators { .. })
+    }
+
+    pub async fn run(
+        self,
+        connection: ConnectionOptions,
+        _signer: ChainXSigner,
+        output: OutputType,
+    ) -> Result<()> {
+        let client = build_client(&connection).await?;
 
         match self {
             Self::Validators { block_number } => {
                 let at = block_hash(&client, block_number).await?;
-                println!("{:#?}", client.validators(at).await?);
+                let validators = client.validators(at).await?;
+                output.print(&validators, || println!("{:#?}", validators))?;
             }
             Self::SetKeys { keys } => {
                 let _ = keys;
@@ -0,0 +1,74 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use sp_core::{storage::StorageKey, twox_128};
+use structopt::StructOpt;
+
+use crate::{
+    connection::ConnectionOptions, output::OutputType, rpc::Rpc, runtime::primitives::BlockNumber,
+    state::StateFile,
+};
+
+/// State files
+#[derive(Debug, StructOpt)]
+pub enum State {
+    /// Dump the storage of the blocks to a file, for the offline analysis with `--state`.
+    Dump {
+        /// Number of the block to dump, can be repeated. Default is the latest block.
+        #[structopt(long = "block")]
+        blocks: Vec<BlockNumber>,
+        /// Only dump the storage of the pallet, e.g. `XStaking`, can be repeated.
+        ///
+        /// Default is the full storage.
+        #[structopt(long = "pallet")]
+        pallets: Vec<String>,
+        /// The state file to write.
+        #[structopt(index = 1, parse(from_os_str))]
+        path: PathBuf,
+    },
+}
+
+impl State {
+    pub async fn run(self, connection: ConnectionOptions, output: OutputType) -> Result<()> {
+        match self {
+            Self::Dump {
+                blocks,
+                pallets,
+                path,
+            } => {
+                let rpc = Rpc::new(&connection).await?;
+                let prefixes = pallets
+                    .iter()
+                    .map(|pallet| StorageKey(twox_128(pallet.as_bytes()).to_vec()))
+                    .collect::<Vec<_>>();
+                let state = StateFile::dump(&rpc, &blocks, &prefixes).await?;
+                state.save(&path)?;
+
+                let dumped = state
+                    .blocks
+                    .iter()
+                    .map(|block| {
+                        serde_json::json!({
+                            "number": block.header.number,
+                            "hash": block.hash,
+                            "entries": block.storage.len(),
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                let json = serde_json::json!({ "path": path, "blocks": dumped });
+                output.print(&json, || {
+                    for block in &state.blocks {
+                        println!(
+                            "Block #{} {:?}: {} storage entries",
+                            block.header.number,
+                            block.hash,
+                            block.storage.len()
+                        );
+                    }
+                    println!("Written to {}", path.display());
+                })?;
+            }
+        }
+        Ok(())
+    }
+}
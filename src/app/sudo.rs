@@ -3,12 +3,14 @@ use std::{marker::PhantomData, path::PathBuf};
 use anyhow::Result;
 use structopt::StructOpt;
 use subxt::{
-    sudo::{SudoCallExt, SudoUncheckedWeightCallExt},
+    sudo::{SudoCall, SudoUncheckedWeightCall},
     system::{SetCodeCall, SetCodeWithoutChecksCall},
     Encoded,
 };
 
 use crate::{
+    connection::ConnectionOptions,
+    extrinsic::{Submitter, TxOptions},
     runtime::{
         primitives::*,
         xpallets::xstaking::{SetSessionsPerEraCall, SetValidatorCountCall},
@@ -62,7 +64,7 @@ impl Calls {
         match self {
             Self::System(system) => match system {
                 System::SetCode { code } => {
-                    println!("System::SetCode:");
+                    log::info!("System::SetCode");
                     let code = read_code(code)?;
                     Ok(client.encode(SetCodeCall::<ChainXRuntime> {
                         _runtime: PhantomData,
@@ -70,7 +72,7 @@ impl Calls {
                     })?)
                 }
                 System::SetCodeWithoutChecks { code } => {
-                    println!("System::SetCodeWithoutChecks:");
+                    log::info!("System::SetCodeWithoutChecks");
                     let code = read_code(code)?;
                     Ok(client.encode(SetCodeWithoutChecksCall::<ChainXRuntime> {
                         _runtime: PhantomData,
@@ -80,14 +82,14 @@ impl Calls {
             },
             Self::XStaking(xstaking) => match xstaking {
                 XStaking::SetValidatorCount { new } => {
-                    println!("sudo XStaking::SetValidatorCount:");
+                    log::info!("sudo XStaking::SetValidatorCount");
                     Ok(client.encode(SetValidatorCountCall::<ChainXRuntime> {
                         _runtime: PhantomData,
                         new: *new,
                     })?)
                 }
                 XStaking::SetSessionsPerEra { new } => {
-                    println!("sudo XStaking::SetSessionsPerEra:");
+                    log::info!("sudo XStaking::SetSessionsPerEra");
                     Ok(client.encode(SetSessionsPerEraCall::<ChainXRuntime> {
                         _runtime: PhantomData,
                         new: *new,
@@ -99,22 +101,32 @@ impl Calls {
 }
 
 impl Sudo {
-    pub async fn run(self, url: String, signer: ChainXSigner) -> Result<()> {
-        let client = build_client(url).await?;
+    pub async fn run(
+        self,
+        connection: ConnectionOptions,
+        signer: ChainXSigner,
+        options: TxOptions,
+    ) -> Result<()> {
+        let client = build_client(&connection).await?;
+        let submitter = Submitter::new(client.clone(), &connection, signer, options).await?;
 
-        println!("Sudo");
         match self {
             Self::Sudo(calls) => {
                 let call = calls.as_encoded(&client)?;
-                let result = client.sudo_and_watch(&signer, &call).await?;
-                println!("{:#?}", result);
+                let call = SudoCall::<ChainXRuntime> {
+                    _runtime: PhantomData,
+                    call: &call,
+                };
+                submitter.submit(call).await?;
             }
             Self::SudoUncheckedWeight(calls) => {
                 let call = calls.as_encoded(&client)?;
-                let result = client
-                    .sudo_unchecked_weight_and_watch(&signer, &call, 0u64)
-                    .await?;
-                println!("{:#?}", result);
+                let call = SudoUncheckedWeightCall::<ChainXRuntime> {
+                    _runtime: PhantomData,
+                    call: &call,
+                    weight: 0u64,
+                };
+                submitter.submit(call).await?;
             }
         }
 
@@ -1,13 +1,16 @@
-use std::path::PathBuf;
+use std::{marker::PhantomData, path::PathBuf};
 
 use anyhow::Result;
 use structopt::StructOpt;
-use subxt::system::{AccountStoreExt, SetCodeWithoutChecksCallExt};
+use subxt::system::{AccountStoreExt, SetCodeWithoutChecksCall};
 
 use crate::{
+    connection::ConnectionOptions,
+    extrinsic::{Submitter, TxOptions},
+    output::AccountInfoView,
     runtime::{
         primitives::{AccountId, BlockNumber},
-        ChainXSigner,
+        ChainXRuntime, ChainXSigner,
     },
     utils::{block_hash, build_client, parse_account, read_code},
 };
@@ -31,20 +34,40 @@ pub enum System {
 }
 
 impl System {
-    pub async fn run(self, url: String, signer: ChainXSigner) -> Result<()> {
-        let client = build_client(url).await?;
+    /// Whether the command signs with the signer.
+    pub fn signs(&self) -> bool {
+        !matches!(self, Self::AccountInfo { .. })
+    }
+
+    pub async fn run(
+        self,
+        connection: ConnectionOptions,
+        signer: ChainXSigner,
+        options: TxOptions,
+    ) -> Result<()> {
+        let client = build_client(&connection).await?;
+        let output = options.output;
+        let submitter = Submitter::new(client.clone(), &connection, signer, options).await?;
 
         match self {
             Self::AccountInfo { who, block_number } => {
                 let at = block_hash(&client, block_number).await?;
                 let account_info = client.account(&who, at).await?;
-                println!("AccountInfo of {:?}: {:#?}", who, account_info);
+                let json = serde_json::json!({
+                    "who": who,
+                    "accountInfo": AccountInfoView::from(&account_info),
+                });
+                output.print(&json, || {
+                    println!("AccountInfo of {:?}: {:#?}", who, account_info)
+                })?;
             }
             Self::SetCodeWithoutChecks { code } => {
-                let result = client
-                    .set_code_without_checks_and_watch(&signer, &read_code(code)?)
-                    .await?;
-                println!("set_code_without_checks result:{:#?}", result);
+                let code = read_code(code)?;
+                let call = SetCodeWithoutChecksCall::<ChainXRuntime> {
+                    _runtime: PhantomData,
+                    code: &code,
+                };
+                submitter.submit(call).await?;
             }
         }
 
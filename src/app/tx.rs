@@ -0,0 +1,227 @@
+use std::{marker::PhantomData, path::PathBuf};
+
+use anyhow::Result;
+use serde_json::Value as JsonValue;
+use structopt::StructOpt;
+use subxt::{balances::TransferCall as BalancesTransferCall, sudo::SudoCall, Encoded};
+
+use crate::{
+    amount::Amount,
+    app::sudo,
+    connection::ConnectionOptions,
+    extrinsic::{dry_run, events_decoder, submit_and_wait, TxOptions, UnsignedTransaction},
+    output::OutputType,
+    rpc::Rpc,
+    runtime::{
+        primitives::{AccountId, AssetId},
+        xpallets::{
+            xassets::TransferCall as XAssetsTransferCall,
+            xstaking::{BondCall, UnbondCall},
+        },
+        ChainXClient, ChainXRuntime, ChainXSigner,
+    },
+    utils::{build_client, parse_account},
+};
+
+/// Offline transaction
+#[derive(Debug, StructOpt)]
+pub enum Tx {
+    /// Build an unsigned transaction of the signer on a connected machine.
+    Build {
+        /// The account that will sign the transaction offline.
+        #[structopt(long, parse(try_from_str = parse_account))]
+        signer: AccountId,
+        /// Write the unsigned transaction to the file instead of stdout.
+        #[structopt(long, parse(from_os_str))]
+        out_file: Option<PathBuf>,
+        #[structopt(subcommand)]
+        call: Call,
+    },
+    /// Sign an unsigned transaction, no network connection is required.
+    Sign {
+        /// The unsigned transaction file.
+        #[structopt(index = 1, parse(from_os_str))]
+        unsigned: PathBuf,
+        /// Write the signed extrinsic to the file instead of stdout.
+        #[structopt(long, parse(from_os_str))]
+        out_file: Option<PathBuf>,
+    },
+    /// Broadcast a signed extrinsic.
+    Submit {
+        /// The hex encoded signed extrinsic, or the file that contains it.
+        #[structopt(index = 1)]
+        extrinsic: String,
+    },
+}
+
+/// Calls that can be built into an unsigned transaction.
+#[derive(Debug, StructOpt)]
+pub enum Call {
+    /// Transfer some balances to another account.
+    BalancesTransfer {
+        #[structopt(index = 1, long, parse(try_from_str = parse_account))]
+        dest: AccountId,
+        #[structopt(index = 2)]
+        value: Amount,
+    },
+    /// Transfer some assets to another account.
+    XAssetsTransfer {
+        #[structopt(index = 1, long, parse(try_from_str = parse_account))]
+        dest: AccountId,
+        #[structopt(index = 2)]
+        asset_id: AssetId,
+        #[structopt(index = 3)]
+        value: Amount,
+    },
+    /// Bond some balances to a validator.
+    XStakingBond {
+        #[structopt(index = 1, long, parse(try_from_str = parse_account))]
+        target: AccountId,
+        #[structopt(index = 2)]
+        value: Amount,
+    },
+    /// Unbond some balances from a validator.
+    XStakingUnbond {
+        #[structopt(index = 1, long, parse(try_from_str = parse_account))]
+        target: AccountId,
+        #[structopt(index = 2)]
+        value: Amount,
+    },
+    /// Dispatch a call with the sudo permissions.
+    Sudo(sudo::Calls),
+    /// A SCALE encoded call in hex.
+    Raw {
+        #[structopt(index = 1)]
+        call: String,
+    },
+}
+
+impl Call {
+    pub async fn as_encoded(&self, client: &ChainXClient) -> Result<Encoded> {
+        match self {
+            Self::BalancesTransfer { dest, value } => {
+                Ok(client.encode(BalancesTransferCall::<ChainXRuntime> {
+                    to: &dest.clone().into(),
+                    amount: value.pcx()?,
+                })?)
+            }
+            Self::XAssetsTransfer {
+                dest,
+                asset_id,
+                value,
+            } => Ok(client.encode(XAssetsTransferCall::<ChainXRuntime> {
+                dest: &dest.clone().into(),
+                asset_id: *asset_id,
+                value: value.asset(client, *asset_id).await?,
+            })?),
+            Self::XStakingBond { target, value } => {
+                Ok(client.encode(BondCall::<ChainXRuntime> {
+                    target: &target.clone().into(),
+                    value: value.pcx()?,
+                })?)
+            }
+            Self::XStakingUnbond { target, value } => {
+                Ok(client.encode(UnbondCall::<ChainXRuntime> {
+                    target: &target.clone().into(),
+                    value: value.pcx()?,
+                })?)
+            }
+            Self::Sudo(calls) => Ok(client.encode(SudoCall::<ChainXRuntime> {
+                _runtime: PhantomData,
+                call: &calls.as_encoded(client)?,
+            })?),
+            Self::Raw { call } => Ok(Encoded(hex::decode(call.trim_start_matches("0x"))?)),
+        }
+    }
+}
+
+/// Writes `content` to `out_file`, or prints `value` as the `key` field of the JSON document or
+/// `content` as text.
+fn write_or_print(
+    output: OutputType,
+    out_file: Option<PathBuf>,
+    key: &str,
+    value: JsonValue,
+    content: String,
+) -> Result<()> {
+    if let Some(path) = out_file {
+        std::fs::write(&path, content)?;
+        let json = serde_json::json!({ "path": path });
+        output.print(&json, || println!("Written to {}", path.display()))
+    } else {
+        let json = serde_json::json!({ key: value });
+        output.print(&json, || println!("{}", content))
+    }
+}
+
+impl Tx {
+    /// Whether the command signs with the signer.
+    pub fn signs(&self) -> bool {
+        matches!(self, Self::Sign { .. })
+    }
+
+    pub async fn run(
+        self,
+        connection: ConnectionOptions,
+        signer: ChainXSigner,
+        options: TxOptions,
+    ) -> Result<()> {
+        match self {
+            Self::Build {
+                signer: who,
+                out_file,
+                call,
+            } => {
+                let client = build_client(&connection).await?;
+                let rpc = Rpc::new(&connection).await?;
+
+                let call = call.as_encoded(&client).await?;
+                let nonce = match options.nonce {
+                    Some(nonce) => nonce,
+                    None => rpc.account_next_index(&who).await?,
+                };
+                let unsigned = options.build(&rpc, who, call.0, nonce).await?;
+                write_or_print(
+                    options.output,
+                    out_file,
+                    "unsigned",
+                    serde_json::to_value(&unsigned)?,
+                    serde_json::to_string_pretty(&unsigned)?,
+                )?;
+            }
+            Self::Sign { unsigned, out_file } => {
+                let unsigned: UnsignedTransaction =
+                    serde_json::from_slice(&std::fs::read(unsigned)?)?;
+                let extrinsic = unsigned.sign(&signer).await?;
+                let extrinsic = format!("0x{}", hex::encode(extrinsic));
+                write_or_print(
+                    options.output,
+                    out_file,
+                    "extrinsic",
+                    extrinsic.clone().into(),
+                    extrinsic,
+                )?;
+            }
+            Self::Submit { extrinsic } => {
+                let extrinsic = if std::path::Path::new(&extrinsic).is_file() {
+                    std::fs::read_to_string(&extrinsic)?
+                } else {
+                    extrinsic
+                };
+                let extrinsic = hex::decode(extrinsic.trim().trim_start_matches("0x"))?;
+
+                let rpc = Rpc::new(&connection).await?;
+                if options.dry_run {
+                    dry_run(&rpc, extrinsic, options.output).await?;
+                } else {
+                    let client = build_client(&connection).await?;
+                    let decoder = events_decoder(&client);
+                    let outcome = submit_and_wait(&rpc, extrinsic, &options, &decoder).await?;
+                    outcome.print(options.output)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -1,13 +1,19 @@
+use std::collections::BTreeMap;
+
 use anyhow::Result;
 use structopt::StructOpt;
 
 use crate::{
+    amount::{asset_symbol, format_asset, Amount},
+    connection::ConnectionOptions,
+    extrinsic::{Submitter, TxOptions},
+    output::{num_str_map, OutputType},
     runtime::{
-        primitives::{AccountId, AssetId, BlockNumber},
+        primitives::{AccountId, AssetId, Balance, BlockNumber},
         xpallets::xassets::{
-            AssetBalanceStoreExt, TotalAssetBalanceStoreExt, TransferCallExt, TransferEventExt,
+            AssetBalanceStoreExt, AssetType, TotalAssetBalanceStoreExt, TransferCall, TransferEvent,
         },
-        ChainXSigner,
+        ChainXRuntime, ChainXSigner,
     },
     utils::{block_hash, build_client, parse_account},
 };
@@ -23,9 +29,9 @@ pub enum XAssets {
         /// asset id
         #[structopt(index = 2)]
         asset_id: AssetId,
-        /// amount
+        /// amount, e.g. `0.001X-BTC` or the raw units
         #[structopt(index = 3)]
-        value: u128,
+        value: Amount,
     },
     Storage(Storage),
 }
@@ -49,8 +55,20 @@ pub enum Storage {
 }
 
 impl XAssets {
-    pub async fn run(self, url: String, signer: ChainXSigner) -> Result<()> {
-        let client = build_client(url).await?;
+    /// Whether the command signs with the signer.
+    pub fn signs(&self) -> bool {
+        !matches!(self, Self::Storage(_))
+    }
+
+    pub async fn run(
+        self,
+        connection: ConnectionOptions,
+        signer: ChainXSigner,
+        options: TxOptions,
+    ) -> Result<()> {
+        let client = build_client(&connection).await?;
+        let output = options.output;
+        let submitter = Submitter::new(client.clone(), &connection, signer, options).await?;
 
         match self {
             Self::Transfer {
@@ -58,13 +76,23 @@ impl XAssets {
                 asset_id,
                 value,
             } => {
-                let result = client
-                    .transfer_and_watch(&signer, &dest.into(), asset_id, value)
-                    .await?;
-                if let Some(event) = result.transfer()? {
-                    println!("XAssets transfer success: value: {:?}", event.amount);
-                } else {
-                    println!("Failed to find XAssets::Transfer Event");
+                let call = TransferCall::<ChainXRuntime> {
+                    dest: &dest.into(),
+                    asset_id,
+                    value: value.asset(&client, asset_id).await?,
+                };
+                let outcome = submitter.submit(call).await?;
+                let text = output == OutputType::Text;
+                if let Some(outcome) = outcome.filter(|outcome| text && outcome.is_included()) {
+                    if let Some(event) = outcome.find_event::<TransferEvent<ChainXRuntime>>()? {
+                        let (symbol, decimals) = asset_symbol(&client, asset_id).await?;
+                        println!(
+                            "XAssets transfer success: value: {}",
+                            format_asset(event.amount, &symbol, decimals)?
+                        );
+                    } else {
+                        println!("Failed to find XAssets::Transfer Event");
+                    }
                 }
             }
             Self::Storage(storage) => match storage {
@@ -75,7 +103,17 @@ impl XAssets {
                 } => {
                     let at = block_hash(&client, block_number).await?;
                     let asset_balance = client.asset_balance(&account_id, asset_id, at).await?;
-                    println!("AssetBalance of {:?}: {:#?}", account_id, asset_balance);
+                    let json = serde_json::json!({
+                        "who": account_id,
+                        "assetId": asset_id,
+                        "balance": num_str_map(&asset_balance),
+                    });
+                    let (symbol, decimals) = asset_symbol(&client, asset_id).await?;
+                    let text = format_balances(&asset_balance, &symbol, decimals)?;
+                    output.print(&json, || {
+                        println!("AssetBalance of {:?}:", account_id);
+                        print!("{}", text);
+                    })?;
                 }
                 Storage::TotalAssetBalance {
                     asset_id,
@@ -83,10 +121,16 @@ impl XAssets {
                 } => {
                     let at = block_hash(&client, block_number).await?;
                     let total_asset_balance = client.total_asset_balance(asset_id, at).await?;
-                    println!(
-                        "TotalAssetBalance of {:?}: {:#?}",
-                        asset_id, total_asset_balance
-                    );
+                    let json = serde_json::json!({
+                        "assetId": asset_id,
+                        "balance": num_str_map(&total_asset_balance),
+                    });
+                    let (symbol, decimals) = asset_symbol(&client, asset_id).await?;
+                    let text = format_balances(&total_asset_balance, &symbol, decimals)?;
+                    output.print(&json, || {
+                        println!("TotalAssetBalance of {:?}:", asset_id);
+                        print!("{}", text);
+                    })?;
                 }
             },
         }
@@ -94,3 +138,20 @@ impl XAssets {
         Ok(())
     }
 }
+
+/// Formats the balances of each asset type, one per line.
+fn format_balances(
+    balances: &BTreeMap<AssetType, Balance>,
+    symbol: &str,
+    decimals: u8,
+) -> Result<String> {
+    let mut text = String::new();
+    for (ty, balance) in balances {
+        text += &format!(
+            "  {:?}: {}\n",
+            ty,
+            format_asset(*balance, symbol, decimals)?
+        );
+    }
+    Ok(text)
+}
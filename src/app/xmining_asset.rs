@@ -1,13 +1,19 @@
+use std::marker::PhantomData;
+
 use anyhow::Result;
 use structopt::StructOpt;
 
 use crate::{
+    amount::{asset_symbol, format_asset, format_pcx},
+    connection::ConnectionOptions,
+    extrinsic::{Submitter, TxOptions},
+    output::OutputType,
     runtime::{
         primitives::{AccountId, AssetId, BlockNumber},
         xpallets::xmining_asset::{
-            AssetLedgersStoreExt, ClaimCallExt, ClaimEventExt, MinerLedgersStoreExt,
+            AssetLedgersStoreExt, ClaimCall, ClaimEvent, MinerLedgersStoreExt,
         },
-        ChainXSigner,
+        ChainXRuntime, ChainXSigner,
     },
     utils::{block_hash, build_client, parse_account},
 };
@@ -43,16 +49,38 @@ pub enum Storage {
 }
 
 impl XMingAsset {
-    pub async fn run(self, url: String, signer: ChainXSigner) -> Result<()> {
-        let client = build_client(url).await?;
+    /// Whether the command signs with the signer.
+    pub fn signs(&self) -> bool {
+        !matches!(self, Self::Storage(_))
+    }
+
+    pub async fn run(
+        self,
+        connection: ConnectionOptions,
+        signer: ChainXSigner,
+        options: TxOptions,
+    ) -> Result<()> {
+        let client = build_client(&connection).await?;
+        let output = options.output;
+        let submitter = Submitter::new(client.clone(), &connection, signer, options).await?;
 
         match self {
             Self::Claim { asset_id } => {
-                let result = client.claim_and_watch(&signer, asset_id).await?;
-                if let Some(event) = result.claim()? {
-                    println!("XMingAsset claim success: value: {:?}", event.amount);
-                } else {
-                    println!("Failed to find XMiningAsset::Claim Event");
+                let call = ClaimCall::<ChainXRuntime> {
+                    _runtime: PhantomData,
+                    target: asset_id,
+                };
+                let outcome = submitter.submit(call).await?;
+                let text = output == OutputType::Text;
+                if let Some(outcome) = outcome.filter(|outcome| text && outcome.is_included()) {
+                    if let Some(event) = outcome.find_event::<ClaimEvent<ChainXRuntime>>()? {
+                        println!(
+                            "XMingAsset claim success: value: {}",
+                            format_pcx(event.amount)
+                        );
+                    } else {
+                        println!("Failed to find XMiningAsset::Claim Event");
+                    }
                 }
             }
             Self::Storage(storage) => match storage {
@@ -62,7 +90,21 @@ impl XMingAsset {
                 } => {
                     let at = block_hash(&client, block_number).await?;
                     let asset_ledgers = client.asset_ledgers(asset_id, at).await?;
-                    println!("AssetLedgers of {:?}: {:#?}", asset_id, asset_ledgers);
+                    let json = serde_json::json!({
+                        "assetId": asset_id,
+                        "ledger": asset_ledgers,
+                    });
+                    let (symbol, decimals) = asset_symbol(&client, asset_id).await?;
+                    let weight =
+                        format_asset(asset_ledgers.last_total_mining_weight, &symbol, decimals)?;
+                    output.print(&json, || {
+                        println!("AssetLedgers of {:?}:", asset_id);
+                        println!("  Last total mining weight: {}", weight);
+                        println!(
+                            "  Last updated at: #{}",
+                            asset_ledgers.last_total_mining_weight_update
+                        );
+                    })?;
                 }
                 Storage::MinerLedgers {
                     account_id,
@@ -71,7 +113,25 @@ impl XMingAsset {
                 } => {
                     let at = block_hash(&client, block_number).await?;
                     let miner_ledgers = client.miner_ledgers(&account_id, asset_id, at).await?;
-                    println!("MinerLedgers of {:?}: {:#?}", asset_id, miner_ledgers);
+                    let json = serde_json::json!({
+                        "who": account_id,
+                        "assetId": asset_id,
+                        "ledger": miner_ledgers,
+                    });
+                    let (symbol, decimals) = asset_symbol(&client, asset_id).await?;
+                    let weight = format_asset(miner_ledgers.last_mining_weight, &symbol, decimals)?;
+                    output.print(&json, || {
+                        println!("MinerLedgers of {:?} in asset {}:", account_id, asset_id);
+                        println!("  Last mining weight: {}", weight);
+                        println!(
+                            "  Last updated at: #{}",
+                            miner_ledgers.last_mining_weight_update
+                        );
+                        match miner_ledgers.last_claim {
+                            Some(block) => println!("  Last claim: #{}", block),
+                            None => println!("  Last claim: never"),
+                        }
+                    })?;
                 }
             },
         }
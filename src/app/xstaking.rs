@@ -1,17 +1,25 @@
-use anyhow::Result;
+use std::{marker::PhantomData, time::Duration};
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
 use structopt::StructOpt;
-use subxt::system::AccountStoreExt;
+use subxt::{system::AccountStoreExt, Signer};
 
 use crate::{
+    amount::{format_pcx, Amount},
+    connection::ConnectionOptions,
+    extrinsic::{Submitter, TxOptions},
+    output::{num_str_map, AccountInfoView, NumStr, OutputType},
     rpc::Rpc,
     runtime::{
         primitives::{AccountId, Balance, BlockNumber},
         xpallets::xstaking::{
-            BondCallExt, ChillCallExt, LocksStoreExt, NominationsStoreExt, RebondCallExt,
-            RegisterCallExt, SetValidatorCountCallExt, UnbondCallExt, ValidateCallExt,
-            ValidatorLedgersStoreExt, ValidatorsStoreExt,
+            BondCall, ChillCall, ClaimCall, ClaimedEvent, LocksStoreExt, NominationsStoreExt,
+            RebondCall, RegisterCall, SetValidatorCountCall, UnbondCall, Unbonded, UnbondedIndex,
+            ValidateCall, ValidatorLedgersStoreExt, ValidatorsStoreExt, WithdrawUnbondedCall,
+            WithdrawnEvent,
         },
-        ChainXSigner,
+        ChainXClient, ChainXRuntime, ChainXSigner,
     },
     utils::{block_hash, build_client, parse_account},
 };
@@ -24,21 +32,21 @@ pub enum XStaking {
         /// Validator nickname
         #[structopt(index = 1, long)]
         nickname: String,
-        /// Initial validator bond
+        /// Initial validator bond, e.g. `100PCX` or the raw units
         #[structopt(index = 2, long)]
-        initial_bond: Balance,
+        initial_bond: Amount,
     },
     Bond {
         #[structopt(index = 1, long, parse(try_from_str = parse_account))]
         target: AccountId,
         #[structopt(index = 2, long)]
-        value: Balance,
+        value: Amount,
     },
     Unbond {
         #[structopt(index = 1, long, parse(try_from_str = parse_account))]
         target: AccountId,
         #[structopt(index = 2, long)]
-        value: Balance,
+        value: Amount,
     },
     Rebond {
         #[structopt(index = 1, long, parse(try_from_str = parse_account))]
@@ -46,7 +54,38 @@ pub enum XStaking {
         #[structopt(index = 2, long, parse(try_from_str = parse_account))]
         to: AccountId,
         #[structopt(index = 3, long)]
-        value: Balance,
+        value: Amount,
+    },
+    /// Claim the staking dividend from a validator.
+    Claim {
+        #[structopt(index = 1, long, parse(try_from_str = parse_account))]
+        target: AccountId,
+    },
+    /// Claim the staking dividend from every validator nominated by the signer.
+    ClaimAll {
+        /// Skip the validators whose dividend is below the threshold, e.g. `0.1PCX`.
+        #[structopt(long, default_value = "0")]
+        threshold: Amount,
+    },
+    /// Withdraw the unbonded funds of the signer from a validator.
+    WithdrawUnbonded {
+        #[structopt(index = 1, long, parse(try_from_str = parse_account))]
+        target: AccountId,
+        /// Indices of the unbonded chunks to withdraw, all the withdrawable chunks by default.
+        ///
+        /// Use `xstaking unbonding-status` to list the chunks.
+        #[structopt(long = "index")]
+        indices: Vec<UnbondedIndex>,
+    },
+    /// List the unbonding chunks of a staker and when they can be withdrawn.
+    UnbondingStatus {
+        #[structopt(index = 1, long, parse(try_from_str = parse_account))]
+        who: AccountId,
+        #[structopt(long)]
+        block_number: Option<BlockNumber>,
+        /// Expected block time in seconds, used to estimate the time until the unlock.
+        #[structopt(long, default_value = "6")]
+        block_time: u64,
     },
     Validate,
     Chill,
@@ -105,118 +144,530 @@ pub enum Storage {
     },
 }
 
+/// An unbonded chunk of a staker, listed by `xstaking unbonding-status`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UnbondingChunk {
+    validator: AccountId,
+    /// Index of the chunk in the ledger of the validator, as passed to `withdraw-unbonded`.
+    index: UnbondedIndex,
+    value: NumStr<Balance>,
+    locked_until: BlockNumber,
+    remaining_blocks: BlockNumber,
+    /// Estimated seconds until the unlock, based on the expected block time.
+    estimated_secs: u64,
+    withdrawable: bool,
+}
+
+/// Returns true if the chunk can be withdrawn by an extrinsic submitted after block `current`.
+fn is_withdrawable(chunk: &Unbonded<Balance, BlockNumber>, current: BlockNumber) -> bool {
+    // The extrinsic is included in a block after `current`.
+    chunk.locked_until <= current
+}
+
+/// Formats the duration as days, hours and minutes, e.g. `1d 2h 3m`.
+fn format_duration(duration: Duration) -> String {
+    let minutes = (duration.as_secs() + 59) / 60;
+    let (days, hours, minutes) = (minutes / 1440, minutes / 60 % 24, minutes % 60);
+    if days > 0 {
+        format!("{}d {}h {}m", days, hours, minutes)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
 impl XStaking {
-    pub async fn run(self, url: String, signer: ChainXSigner) -> Result<()> {
-        let client = build_client(url.clone()).await?;
+    /// Whether the command signs with the signer.
+    pub fn signs(&self) -> bool {
+        !matches!(
+            self,
+            Self::GetDividend { .. }
+                | Self::GetNomination { .. }
+                | Self::CheckStaker { .. }
+                | Self::UnbondingStatus { .. }
+                | Self::Storage(_)
+        )
+    }
+
+    pub async fn run(
+        self,
+        connection: ConnectionOptions,
+        signer: ChainXSigner,
+        options: TxOptions,
+    ) -> Result<()> {
+        let client = build_client(&connection).await?;
+        let output = options.output;
+        if !self.signs() {
+            let rpc = Rpc::new(&connection).await?;
+            return self.query(&client, &rpc, output).await;
+        }
+
+        let who = signer.account_id().clone();
+        let submitter = Submitter::new(client.clone(), &connection, signer, options).await?;
 
         match self {
             Self::Register {
                 nickname,
                 initial_bond,
             } => {
-                let result = client
-                    .register_and_watch(&signer, nickname.as_bytes().to_vec(), initial_bond)
-                    .await?;
-                println!("register result:{:#?}", result);
+                let call = RegisterCall::<ChainXRuntime> {
+                    _runtime: PhantomData,
+                    validator_nickname: nickname.as_bytes().to_vec(),
+                    initial_bond: initial_bond.pcx()?,
+                };
+                submitter.submit(call).await?;
             }
             Self::Bond { target, value } => {
-                let result = client
-                    .bond_and_watch(&signer, &target.into(), value)
-                    .await?;
-                println!("bond result:{:#?}", result);
+                let call = BondCall::<ChainXRuntime> {
+                    target: &target.into(),
+                    value: value.pcx()?,
+                };
+                submitter.submit(call).await?;
             }
             Self::Unbond { target, value } => {
-                let result = client
-                    .unbond_and_watch(&signer, &target.into(), value)
-                    .await?;
-                println!("unbond result:{:#?}", result);
+                let call = UnbondCall::<ChainXRuntime> {
+                    target: &target.into(),
+                    value: value.pcx()?,
+                };
+                submitter.submit(call).await?;
             }
             Self::Rebond { from, to, value } => {
-                let result = client
-                    .rebond_and_watch(&signer, &from.into(), &to.into(), value)
-                    .await?;
-                println!("rebond result:{:#?}", result);
+                let call = RebondCall::<ChainXRuntime> {
+                    from: &from.into(),
+                    to: &to.into(),
+                    value: value.pcx()?,
+                };
+                submitter.submit(call).await?;
+            }
+            Self::Claim { target } => {
+                let call = ClaimCall::<ChainXRuntime> {
+                    target: &target.into(),
+                };
+                let outcome = submitter.submit(call).await?;
+                let text = output == OutputType::Text;
+                if let Some(outcome) = outcome.filter(|outcome| text && outcome.is_included()) {
+                    if let Some(event) = outcome.find_event::<ClaimedEvent<ChainXRuntime>>()? {
+                        println!(
+                            "XStaking claim success: value: {}",
+                            format_pcx(event.amount)
+                        );
+                    } else {
+                        println!("Failed to find XStaking::Claimed Event");
+                    }
+                }
+            }
+            Self::ClaimAll { threshold } => {
+                let threshold = threshold.pcx()?;
+                let rpc = submitter.rpc();
+                let dividend = rpc.get_staking_dividend(who.clone(), None).await?;
+
+                let mut claimed = Vec::new();
+                let mut skipped = Vec::new();
+                let mut failed = 0;
+                let mut total = 0;
+                for (validator, amount) in dividend {
+                    if amount == 0 || amount < threshold {
+                        skipped.push(serde_json::json!({
+                            "validator": validator,
+                            "dividend": NumStr(amount),
+                        }));
+                        continue;
+                    }
+                    let claim = async {
+                        let call = ClaimCall::<ChainXRuntime> {
+                            target: &validator.clone().into(),
+                        };
+                        let submission = submitter.submit_quietly(call).await?;
+                        // Nothing is received in the dry run or without waiting for the inclusion.
+                        let reward =
+                            match submission.outcome().filter(|outcome| outcome.is_included()) {
+                                Some(outcome) => outcome
+                                    .find_event::<ClaimedEvent<ChainXRuntime>>()?
+                                    .map(|event| event.amount),
+                                None => None,
+                            };
+                        Ok::<_, anyhow::Error>((submission, reward))
+                    };
+                    // A failed claim doesn't stop the sweep, it's reported with the others.
+                    match claim.await {
+                        Ok((submission, reward)) => {
+                            if output == OutputType::Text {
+                                submission.print(output)?;
+                            }
+                            total += reward.unwrap_or_default();
+                            claimed.push(serde_json::json!({
+                                "validator": validator,
+                                "dividend": NumStr(amount),
+                                "reward": reward.map(NumStr),
+                                "submission": submission,
+                            }));
+                        }
+                        Err(err) => {
+                            if output == OutputType::Text {
+                                println!("Failed to claim from {:?}: {}", validator, err);
+                            }
+                            failed += 1;
+                            claimed.push(serde_json::json!({
+                                "validator": validator,
+                                "dividend": NumStr(amount),
+                                "error": err.to_string(),
+                            }));
+                        }
+                    }
+                }
+
+                let json = serde_json::json!({
+                    "who": who,
+                    "threshold": NumStr(threshold),
+                    "claimed": claimed,
+                    "skipped": skipped,
+                    "totalReward": NumStr(total),
+                });
+                output.print(&json, || {
+                    println!(
+                        "Claimed from {} validator(s), {} failed, skipped {} below {}",
+                        claimed.len() - failed,
+                        failed,
+                        skipped.len(),
+                        format_pcx(threshold)
+                    );
+                    println!("Total reward received: {}", format_pcx(total));
+                })?;
+                if failed > 0 {
+                    return Err(anyhow!("Failed to claim from {} validator(s)", failed));
+                }
+            }
+            Self::WithdrawUnbonded { target, indices } => {
+                let rpc = submitter.rpc();
+                let current = rpc.latest_block_number().await?;
+                let ledger = client.nominations(&who, &target, None).await?;
+                let chunks = ledger.unbonded_chunks;
+
+                let mut indices = if indices.is_empty() {
+                    (0..chunks.len() as UnbondedIndex)
+                        .filter(|&index| is_withdrawable(&chunks[index as usize], current))
+                        .collect::<Vec<_>>()
+                } else {
+                    for &index in &indices {
+                        let chunk = chunks.get(index as usize).ok_or_else(|| {
+                            anyhow!(
+                                "Unbonded chunk #{} not found, {:?} has {} chunk(s) from {:?}",
+                                index,
+                                who,
+                                chunks.len(),
+                                target
+                            )
+                        })?;
+                        if !is_withdrawable(chunk, current) {
+                            return Err(anyhow!(
+                                "Unbonded chunk #{} is locked until block #{}, current block #{}",
+                                index,
+                                chunk.locked_until,
+                                current
+                            ));
+                        }
+                    }
+                    indices
+                };
+                if indices.is_empty() {
+                    return Err(anyhow!(
+                        "No unbonded chunk from {:?} is withdrawable at block #{}",
+                        target,
+                        current
+                    ));
+                }
+                // The runtime swap-removes the withdrawn chunk, i.e., moves the last chunk into its
+                // slot. Withdrawn from the highest index, every remaining index is lower than the
+                // removed one, and so never refers to the moved chunk.
+                indices.sort_unstable_by(|a, b| b.cmp(a));
+                indices.dedup();
+
+                let mut withdrawn = Vec::new();
+                let mut total = 0;
+                let mut error = None;
+                for index in indices {
+                    let withdraw = async {
+                        let call = WithdrawUnbondedCall::<ChainXRuntime> {
+                            target: &target.clone().into(),
+                            unbonded_index: index,
+                        };
+                        let submission = submitter.submit_quietly(call).await?;
+                        let amount =
+                            match submission.outcome().filter(|outcome| outcome.is_included()) {
+                                Some(outcome) => outcome
+                                    .find_event::<WithdrawnEvent<ChainXRuntime>>()?
+                                    .map(|event| event.amount),
+                                None => None,
+                            };
+                        Ok::<_, anyhow::Error>((submission, amount))
+                    };
+                    match withdraw.await {
+                        Ok((submission, amount)) => {
+                            if output == OutputType::Text {
+                                submission.print(output)?;
+                            }
+                            total += amount.unwrap_or_default();
+                            withdrawn.push(serde_json::json!({
+                                "index": index,
+                                "value": NumStr(chunks[index as usize].value),
+                                "withdrawn": amount.map(NumStr),
+                                "submission": submission,
+                            }));
+                        }
+                        // The chunks after a failed withdrawal may have been moved, stop here.
+                        Err(err) => {
+                            error = Some(err);
+                            break;
+                        }
+                    }
+                }
+
+                let json = serde_json::json!({
+                    "who": who,
+                    "target": target,
+                    "chunks": withdrawn,
+                    "totalWithdrawn": NumStr(total),
+                    "error": error.as_ref().map(ToString::to_string),
+                });
+                output.print(&json, || {
+                    println!(
+                        "Withdrew {} unbonded chunk(s) from {:?}",
+                        withdrawn.len(),
+                        target
+                    );
+                    println!("Total withdrawn: {}", format_pcx(total));
+                })?;
+                if let Some(err) = error {
+                    return Err(err.context("Failed to withdraw the unbonded chunks"));
+                }
             }
             Self::Validate => {
-                let result = client.validate_and_watch(&signer).await?;
-                println!("validate result:{:#?}", result);
+                let call = ValidateCall::<ChainXRuntime> {
+                    _runtime: PhantomData,
+                };
+                submitter.submit(call).await?;
             }
             Self::Chill => {
-                let result = client.chill_and_watch(&signer).await?;
-                println!("chill result:{:#?}", result);
+                let call = ChillCall::<ChainXRuntime> {
+                    _runtime: PhantomData,
+                };
+                submitter.submit(call).await?;
             }
             Self::SetValidatorCount { new } => {
-                let result = client.set_validator_count_and_watch(&signer, new).await?;
-                println!("set_validator_count result:{:#?}", result);
+                let call = SetValidatorCountCall::<ChainXRuntime> {
+                    _runtime: PhantomData,
+                    new,
+                };
+                submitter.submit(call).await?;
+            }
+            _ => unreachable!("The read-only commands are queried without the signer"),
+        }
+
+        Ok(())
+    }
+
+    /// Runs the read-only commands.
+    async fn query(self, client: &ChainXClient, rpc: &Rpc, output: OutputType) -> Result<()> {
+        match self {
+            Self::UnbondingStatus {
+                who,
+                block_number,
+                block_time,
+            } => {
+                let at = block_hash(client, block_number).await?;
+                let current = rpc
+                    .header(at)
+                    .await?
+                    .ok_or_else(|| anyhow!("Header of block {:?} not found", at))?
+                    .number;
+                let nominations = rpc.get_nominations_rpc(who.clone(), at).await?;
+
+                let mut chunks = Vec::new();
+                let mut total_withdrawable: Balance = 0;
+                for (validator, ledger) in nominations {
+                    for (index, chunk) in ledger.unbonded_chunks.into_iter().enumerate() {
+                        let withdrawable = is_withdrawable(&chunk, current);
+                        if withdrawable {
+                            total_withdrawable += chunk.value;
+                        }
+                        let remaining = chunk.locked_until.saturating_sub(current);
+                        chunks.push(UnbondingChunk {
+                            validator: validator.clone(),
+                            index: index as UnbondedIndex,
+                            value: NumStr(chunk.value),
+                            locked_until: chunk.locked_until,
+                            remaining_blocks: remaining,
+                            estimated_secs: u64::from(remaining) * block_time,
+                            withdrawable,
+                        });
+                    }
+                }
+
+                let json = serde_json::json!({
+                    "who": who,
+                    "blockNumber": current,
+                    "chunks": chunks,
+                    "totalWithdrawable": NumStr(total_withdrawable),
+                });
+                output.print(&json, || {
+                    println!("Unbonding chunks of {:?} at block #{}", who, current);
+                    for chunk in &chunks {
+                        let status = if chunk.withdrawable {
+                            "withdrawable now".to_string()
+                        } else {
+                            format!(
+                                "unlocks in {} blocks, ~{}",
+                                chunk.remaining_blocks,
+                                format_duration(Duration::from_secs(chunk.estimated_secs))
+                            )
+                        };
+                        println!(
+                            "  {:?} #{}: {}, locked until #{}, {}",
+                            chunk.validator,
+                            chunk.index,
+                            format_pcx(chunk.value.0),
+                            chunk.locked_until,
+                            status
+                        );
+                    }
+                    println!("Total withdrawable: {}", format_pcx(total_withdrawable));
+                })?;
             }
             Self::GetDividend { who, block_number } => {
-                let rpc = Rpc::new(url).await?;
-                let at = block_hash(&client, block_number).await?;
+                let at = block_hash(client, block_number).await?;
                 let dividend = rpc.get_staking_dividend(who.clone(), at).await?;
-                println!("Staking dividend of {:?}: {:#?}", who, dividend);
+                let json = serde_json::json!({
+                    "who": who,
+                    "dividend": num_str_map(&dividend),
+                });
+                output.print(&json, || {
+                    println!("Staking dividend of {:?}: {:#?}", who, dividend)
+                })?;
             }
             Self::CheckStaker { who, block_number } => {
-                let rpc = Rpc::new(url).await?;
-                let at = block_hash(&client, block_number).await?;
+                let at = block_hash(client, block_number).await?;
 
                 let nominations = rpc.get_nominations_rpc(who.clone(), at).await?;
-                println!("Nominations of {:?}: {:#?}", who, nominations);
-
                 let locks = client.locks(&who, at).await?;
                 let total_locked = locks.values().sum::<u128>();
-                println!("Locks for {:?}", who);
-                println!("Details: {:#?}", locks);
-                println!("total locked in Staking: {}", total_locked);
-
                 let account_info = client.account(&who, at).await?;
-                println!("AccountInfo of {:?}: {:#?}", who, account_info);
+
+                let json = serde_json::json!({
+                    "who": who,
+                    "nominations": nominations,
+                    "locks": num_str_map(&locks),
+                    "totalLocked": NumStr(total_locked),
+                    "accountInfo": AccountInfoView::from(&account_info),
+                });
+                output.print(&json, || {
+                    println!("Nominations of {:?}: {:#?}", who, nominations);
+                    println!("Locks for {:?}", who);
+                    println!("Details: {:#?}", locks);
+                    println!("total locked in Staking: {}", format_pcx(total_locked));
+                    println!("AccountInfo of {:?}: {:#?}", who, account_info);
+                })?;
             }
             Self::GetNomination { who, block_number } => {
-                let rpc = Rpc::new(url).await?;
-                let at = block_hash(&client, block_number).await?;
+                let at = block_hash(client, block_number).await?;
                 let nominations = rpc.get_nominations_rpc(who.clone(), at).await?;
-                println!("Nominations of {:?}: {:#?}", who, nominations);
+                let json = serde_json::json!({
+                    "who": who,
+                    "nominations": nominations,
+                });
+                output.print(&json, || {
+                    println!("Nominations of {:?}: {:#?}", who, nominations)
+                })?;
             }
             Self::Storage(storage) => match storage {
                 Storage::Validators {
                     validator_id,
                     block_number,
                 } => {
-                    let at = block_hash(&client, block_number).await?;
+                    let at = block_hash(client, block_number).await?;
                     let profile = client.validators(&validator_id, at).await?;
-                    println!("{:?}: {:#?}", validator_id, profile);
+                    let json = serde_json::json!({
+                        "validatorId": validator_id,
+                        "profile": profile,
+                    });
+                    output.print(&json, || println!("{:?}: {:#?}", validator_id, profile))?;
                 }
                 Storage::ValidatorLedgers {
                     validator_id,
                     block_number,
                 } => {
-                    let at = block_hash(&client, block_number).await?;
+                    let at = block_hash(client, block_number).await?;
                     let ledgers = client.validator_ledgers(&validator_id, at).await?;
-                    println!("{:?}: {:#?}", validator_id, ledgers);
+                    let json = serde_json::json!({
+                        "validatorId": validator_id,
+                        "ledger": ledgers,
+                    });
+                    output.print(&json, || println!("{:?}: {:#?}", validator_id, ledgers))?;
                 }
                 Storage::Nominations {
                     nominator,
                     nominatee,
                     block_number,
                 } => {
-                    let at = block_hash(&client, block_number).await?;
+                    let at = block_hash(client, block_number).await?;
                     let ledgers = client.nominations(&nominator, &nominatee, at).await?;
-                    println!("{:?} => {:?}: {:#?}", nominator, nominatee, ledgers);
+                    let json = serde_json::json!({
+                        "nominator": nominator,
+                        "nominatee": nominatee,
+                        "ledger": ledgers,
+                    });
+                    output.print(&json, || {
+                        println!("{:?} => {:?}: {:#?}", nominator, nominatee, ledgers)
+                    })?;
                 }
                 Storage::Locks {
                     staker,
                     block_number,
                 } => {
-                    let at = block_hash(&client, block_number).await?;
+                    let at = block_hash(client, block_number).await?;
                     let locks = client.locks(&staker, at).await?;
                     let total_locked = locks.values().sum::<u128>();
-                    println!("Locks for {:?}", staker);
-                    println!("Details: {:#?}", locks);
-                    println!("total locked in Staking: {}", total_locked);
+                    let json = serde_json::json!({
+                        "who": staker,
+                        "locks": num_str_map(&locks),
+                        "totalLocked": NumStr(total_locked),
+                    });
+                    output.print(&json, || {
+                        println!("Locks for {:?}", staker);
+                        println!("Details: {:#?}", locks);
+                        println!("total locked in Staking: {}", format_pcx(total_locked));
+                    })?;
                 }
             },
+            _ => unreachable!("The signing commands are submitted by `run`"),
         }
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_withdrawable() {
+        let chunk = Unbonded {
+            value: 100,
+            locked_until: 10,
+        };
+        assert!(!is_withdrawable(&chunk, 9));
+        assert!(is_withdrawable(&chunk, 10));
+        assert!(is_withdrawable(&chunk, 11));
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_secs(0)), "0m");
+        assert_eq!(format_duration(Duration::from_secs(1)), "1m");
+        assert_eq!(format_duration(Duration::from_secs(3600)), "1h 0m");
+        assert_eq!(format_duration(Duration::from_secs(93_780)), "1d 2h 3m");
+        assert_eq!(format_duration(Duration::from_secs(93_781)), "1d 2h 4m");
+    }
+}
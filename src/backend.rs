@@ -0,0 +1,134 @@
+//! Backends that serve the JSON-RPC requests of `Rpc`.
+
+use std::{net::SocketAddr, sync::Arc};
+
+use anyhow::{anyhow, Result};
+use async_std::sync::RwLock;
+use futures::future::{self, BoxFuture};
+use jsonrpsee::{
+    client::RequestError,
+    common::{Error as JsonRpcError, ErrorCode, Params},
+    Client,
+};
+use serde_json::Value as JsonValue;
+
+use crate::connection::{ConnectionOptions, Transport, TransportError};
+
+/// Serves the JSON-RPC requests.
+pub trait Backend: Send + Sync {
+    /// Sends the request once and returns the result.
+    ///
+    /// The failures of the connection are returned as `TransportError`, which are retried.
+    fn request<'a>(&'a self, method: &'a str, params: Params) -> BoxFuture<'a, Result<JsonValue>>;
+
+    /// Whether the failed requests may succeed if retried.
+    fn retryable(&self) -> bool {
+        false
+    }
+
+    /// Re-establishes the connection before a request is retried.
+    fn reconnect(&self) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async { Ok(()) })
+    }
+
+    /// Returns the client of the subscriptions.
+    fn subscription_client(&self) -> BoxFuture<'_, Result<Client>> {
+        Box::pin(async { Err(anyhow!("Subscriptions require a live node")) })
+    }
+}
+
+/// A live node, over the transport of the url scheme.
+pub struct NodeBackend {
+    client: RwLock<Client>,
+    options: ConnectionOptions,
+}
+
+impl NodeBackend {
+    /// Connects to the node, retries according to `options`.
+    pub async fn connect(options: &ConnectionOptions) -> Result<Self> {
+        let client = options.connect(|| options.rpc_client()).await?;
+        Ok(Self {
+            client: RwLock::new(client),
+            options: options.clone(),
+        })
+    }
+
+    async fn client(&self) -> Client {
+        self.client.read().await.clone()
+    }
+}
+
+impl Backend for NodeBackend {
+    fn request<'a>(&'a self, method: &'a str, params: Params) -> BoxFuture<'a, Result<JsonValue>> {
+        Box::pin(async move {
+            let client = self.client().await;
+            client
+                .request(method, params)
+                .await
+                .map_err(|err| match err {
+                    RequestError::TransportError(err) => TransportError(err.to_string()).into(),
+                    err => err.into(),
+                })
+        })
+    }
+
+    fn retryable(&self) -> bool {
+        true
+    }
+
+    fn reconnect(&self) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move {
+            let client = self.options.connect(|| self.options.rpc_client()).await?;
+            *self.client.write().await = client;
+            Ok(())
+        })
+    }
+
+    fn subscription_client(&self) -> BoxFuture<'_, Result<Client>> {
+        Box::pin(async move {
+            if self.options.transport()? == Transport::Http {
+                return Err(anyhow!(
+                    "Subscriptions require a websocket url, use `--wait none` over HTTP"
+                ));
+            }
+            Ok(self.client().await)
+        })
+    }
+}
+
+/// Serves `methods` of `backend` on a local websocket server at `addr`, a stand-in of the node.
+///
+/// Never returns unless the server fails to start.
+pub async fn serve_ws(
+    backend: Arc<dyn Backend>,
+    addr: &SocketAddr,
+    methods: &[&str],
+) -> Result<()> {
+    let server = jsonrpsee::ws_server(addr)
+        .await
+        .map_err(|err| anyhow!("Failed to start the server at {}: {}", addr, err))?;
+
+    let mut handlers = Vec::with_capacity(methods.len());
+    for method in methods {
+        let mut registered = server
+            .register_method(method.to_string())
+            .map_err(|_| anyhow!("Method `{}` is registered twice", method))?;
+        let backend = backend.clone();
+        handlers.push(async move {
+            loop {
+                let request = registered.next().await;
+                let response = backend
+                    .request(method, request.params().clone())
+                    .await
+                    .map_err(|err| JsonRpcError {
+                        code: ErrorCode::ServerError(-32000),
+                        message: err.to_string(),
+                        data: None,
+                    });
+                request.respond(response).await;
+            }
+        });
+    }
+    future::join_all(handlers).await;
+    Ok(())
+}
@@ -1,26 +1,33 @@
 //! Analyze the balance history of an account.
+//!
+//! Instead of querying the account at every block, the account states at both ends of a block
+//! range are compared, and the range is bisected until the blocks changing the account are
+//! found. A change reverted within a range, which also keeps the nonce, is not detected.
 
-use std::cmp::Ordering;
+use std::collections::BTreeMap;
 
 use anyhow::Result;
-use sp_runtime::generic::{Block, SignedBlock};
+use codec::Encode;
+use serde::Serialize;
+use serde_json::Value as JsonValue;
 use structopt::StructOpt;
-use subxt::system::{AccountStoreExt, System};
+use subxt::system::Phase;
 
 use chainx_cli::{
-    block_hash, build_client, parse_account,
-    runtime::{
-        primitives::{AccountId, BlockNumber},
-        ChainXClient, ChainXRuntime,
-    },
+    amount::format_pcx,
+    connection::ConnectionOptions,
+    decoder::Decoder,
+    output::{NumStr, OutputType},
+    parse_account,
+    rpc::{BlockId, Rpc},
+    runtime::primitives::{AccountId, Balance, BlockNumber, Hash, Index},
 };
 
 #[derive(StructOpt, Debug)]
 #[structopt(author, about, no_version)]
 struct App {
-    /// The websocket url of ChainX node.
-    #[structopt(long, default_value = "ws://127.0.0.1:8087")]
-    pub url: String,
+    #[structopt(flatten)]
+    pub connection: ConnectionOptions,
 
     /// Account
     #[structopt(short, long, parse(try_from_str = parse_account))]
@@ -34,6 +41,19 @@ struct App {
     #[structopt(long)]
     pub end_block: Option<BlockNumber>,
 
+    /// Print the history as text or a single JSON document.
+    #[structopt(
+        long,
+        default_value = "text",
+        possible_values = &OutputType::variants(),
+        case_insensitive = true
+    )]
+    pub output: OutputType,
+
+    /// Print the history as CSV instead.
+    #[structopt(long, conflicts_with = "output")]
+    pub csv: bool,
+
     /// Ss58 Address version of the network.
     ///
     /// 44 for ChainX mainnet, 42 for Substrate.
@@ -41,14 +61,221 @@ struct App {
     pub ss58_prefix: sp_core::crypto::Ss58AddressFormat,
 }
 
-pub type ChainBlock<T> = SignedBlock<Block<<T as System>::Header, <T as System>::Extrinsic>>;
+/// The tracked state of the account at a block.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+struct AccountState {
+    nonce: Index,
+    free: Balance,
+    reserved: Balance,
+    misc_frozen: Balance,
+    fee_frozen: Balance,
+}
+
+/// A change of the account state.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ChangeRecord {
+    block: BlockNumber,
+    hash: Hash,
+    nonce: Index,
+    free: NumStr<Balance>,
+    reserved: NumStr<Balance>,
+    misc_frozen: NumStr<Balance>,
+    fee_frozen: NumStr<Balance>,
+    free_diff: NumStr<i128>,
+    reserved_diff: NumStr<i128>,
+    misc_frozen_diff: NumStr<i128>,
+    fee_frozen_diff: NumStr<i128>,
+    /// Calls of the extrinsics signed by the account, which paid the fees.
+    extrinsics: Vec<String>,
+    /// Events of the extrinsics signed by the account, and the events mentioning the account.
+    events: Vec<String>,
+}
+
+fn diff(new: Balance, old: Balance) -> NumStr<i128> {
+    NumStr(new as i128 - old as i128)
+}
+
+impl ChangeRecord {
+    fn new(block: BlockNumber, hash: Hash, state: AccountState, last: AccountState) -> Self {
+        Self {
+            block,
+            hash,
+            nonce: state.nonce,
+            free: NumStr(state.free),
+            reserved: NumStr(state.reserved),
+            misc_frozen: NumStr(state.misc_frozen),
+            fee_frozen: NumStr(state.fee_frozen),
+            free_diff: diff(state.free, last.free),
+            reserved_diff: diff(state.reserved, last.reserved),
+            misc_frozen_diff: diff(state.misc_frozen, last.misc_frozen),
+            fee_frozen_diff: diff(state.fee_frozen, last.fee_frozen),
+            extrinsics: Vec::new(),
+            events: Vec::new(),
+        }
+    }
+}
+
+/// Account states of the blocks fetched so far.
+struct History<'a> {
+    rpc: &'a Rpc,
+    who: AccountId,
+    states: BTreeMap<BlockNumber, (Hash, AccountState)>,
+}
+
+impl<'a> History<'a> {
+    async fn state_at(&mut self, number: BlockNumber) -> Result<(Hash, AccountState)> {
+        if let Some(state) = self.states.get(&number) {
+            return Ok(*state);
+        }
+        let hash = self
+            .rpc
+            .block_id_hash(Some(BlockId::Number(number)))
+            .await?;
+        let info = self.rpc.get_account_info(&self.who, Some(hash)).await?;
+        let state = AccountState {
+            nonce: info.nonce,
+            free: info.data.free,
+            reserved: info.data.reserved,
+            misc_frozen: info.data.misc_frozen,
+            fee_frozen: info.data.fee_frozen,
+        };
+        self.states.insert(number, (hash, state));
+        Ok((hash, state))
+    }
 
-async fn latest_block_number(client: &ChainXClient) -> Result<BlockNumber> {
-    let latest_block: ChainBlock<ChainXRuntime> = client
-        .block(None::<<ChainXRuntime as System>::Hash>)
-        .await?
-        .expect("Failed to fetch latest block");
-    Ok(latest_block.block.header.number)
+    /// Returns the blocks in `(start, end]` whose state differs from the parent block.
+    async fn change_points(
+        &mut self,
+        start: BlockNumber,
+        end: BlockNumber,
+    ) -> Result<Vec<BlockNumber>> {
+        let mut changes = Vec::new();
+        let mut ranges = vec![(start, end)];
+        while let Some((low, high)) = ranges.pop() {
+            if high <= low || self.state_at(low).await?.1 == self.state_at(high).await?.1 {
+                continue;
+            }
+            if high == low + 1 {
+                changes.push(high);
+                continue;
+            }
+            let middle = low + (high - low) / 2;
+            ranges.push((middle, high));
+            ranges.push((low, middle));
+        }
+        changes.sort_unstable();
+        Ok(changes)
+    }
+}
+
+fn mentions(value: &JsonValue, who: &str) -> bool {
+    match value {
+        JsonValue::String(value) => value == who,
+        JsonValue::Array(values) => values.iter().any(|value| mentions(value, who)),
+        JsonValue::Object(values) => values.values().any(|value| mentions(value, who)),
+        _ => false,
+    }
+}
+
+/// Returns the calls signed by `who` in block `hash`, the events of those calls and the
+/// events mentioning `who`.
+async fn attribute(
+    rpc: &Rpc,
+    decoders: &mut BTreeMap<u32, Decoder>,
+    who: &AccountId,
+    hash: Hash,
+) -> Result<(Vec<String>, Vec<String>)> {
+    let spec_version = rpc.runtime_version(Some(hash)).await?.spec_version;
+    if !decoders.contains_key(&spec_version) {
+        let decoder = Decoder::new(&rpc.metadata(Some(hash)).await?)?;
+        decoders.insert(spec_version, decoder);
+    }
+    let decoder = &decoders[&spec_version];
+    let who = who.to_string();
+
+    let mut extrinsics = Vec::new();
+    let mut signed = Vec::new();
+    if let Some(block) = rpc.block(Some(hash)).await? {
+        for (index, extrinsic) in block.block.extrinsics.iter().enumerate() {
+            let extrinsic = decoder.decode_extrinsic(&extrinsic.encode())?;
+            if extrinsic.signature.map(|signature| signature.signer) == Some(who.clone()) {
+                extrinsics.push(format!("{}.{}", extrinsic.call.pallet, extrinsic.call.call));
+                signed.push(Phase::ApplyExtrinsic(index as u32));
+            }
+        }
+    }
+
+    let events = decoder
+        .decode_events(&rpc.events(Some(hash)).await?)?
+        .into_iter()
+        .filter(|(phase, event)| {
+            signed.contains(phase) || event.args.iter().any(|arg| mentions(arg, &who))
+        })
+        .map(|(_, event)| format!("{}.{}", event.pallet, event.event))
+        .collect();
+    Ok((extrinsics, events))
+}
+
+/// Quotes the CSV field if necessary.
+fn csv_field(field: String) -> String {
+    if field.contains(|c| c == ',' || c == '"' || c == '\n') {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field
+    }
+}
+
+fn print_csv(records: &[ChangeRecord]) {
+    println!("block,hash,nonce,free,reserved,misc_frozen,fee_frozen,free_diff,reserved_diff,misc_frozen_diff,fee_frozen_diff,extrinsics,events");
+    for record in records {
+        let fields = vec![
+            record.block.to_string(),
+            format!("{:?}", record.hash),
+            record.nonce.to_string(),
+            record.free.0.to_string(),
+            record.reserved.0.to_string(),
+            record.misc_frozen.0.to_string(),
+            record.fee_frozen.0.to_string(),
+            record.free_diff.0.to_string(),
+            record.reserved_diff.0.to_string(),
+            record.misc_frozen_diff.0.to_string(),
+            record.fee_frozen_diff.0.to_string(),
+            record.extrinsics.join(";"),
+            record.events.join(";"),
+        ];
+        let fields = fields.into_iter().map(csv_field).collect::<Vec<_>>();
+        println!("{}", fields.join(","));
+    }
+}
+
+/// Formats the signed diff of PCX, e.g. `+12.5 PCX`.
+fn format_pcx_diff(diff: NumStr<i128>) -> String {
+    let sign = if diff.0 < 0 { '-' } else { '+' };
+    format!("{}{}", sign, format_pcx(diff.0.abs() as Balance))
+}
+
+fn print_text(records: &[ChangeRecord]) {
+    for record in records {
+        println!(
+            "{:>14}, free {} ({}), reserved {} ({}), misc_frozen {} ({}), fee_frozen {} ({})",
+            format!("Block#{}", record.block),
+            format_pcx(record.free.0),
+            format_pcx_diff(record.free_diff),
+            format_pcx(record.reserved.0),
+            format_pcx_diff(record.reserved_diff),
+            format_pcx(record.misc_frozen.0),
+            format_pcx_diff(record.misc_frozen_diff),
+            format_pcx(record.fee_frozen.0),
+            format_pcx_diff(record.fee_frozen_diff),
+        );
+        if !record.extrinsics.is_empty() {
+            println!("{:>14}  extrinsics: {}", "", record.extrinsics.join(", "));
+        }
+        if !record.events.is_empty() {
+            println!("{:>14}  events: {}", "", record.events.join(", "));
+        }
+    }
 }
 
 #[async_std::main]
@@ -59,51 +286,52 @@ async fn main() -> Result<()> {
 
     sp_core::crypto::set_default_ss58_version(sp_core::crypto::Ss58AddressFormat::ChainXAccount);
 
-    let client = build_client(app.url.clone()).await?;
+    // The transport errors are retried, so that a long scan survives the node restarts.
+    let rpc = Rpc::new(&app.connection).await?;
 
     let who = app.who;
     let start_block = app.start_block.unwrap_or(0);
     let end_block = if let Some(block_number) = app.end_block {
         block_number
     } else {
-        latest_block_number(&client).await?
+        rpc.latest_block_number().await?
     };
 
-    let mut last_free = 0;
-    let mut latest_diff = 0;
-
-    for blk in start_block..=end_block {
-        let at = block_hash(&client, Some(blk)).await?;
-        let account_info = client.account(&who, at).await?;
-        let new_free = account_info.data.free;
-        if new_free != last_free {
-            let (sign, diff) = match new_free.cmp(&last_free) {
-                Ordering::Greater => ("[+]", new_free - last_free),
-                Ordering::Less => ("[-]", last_free - new_free),
-                Ordering::Equal => unreachable!("They are not equal as just checked above; qed"),
-            };
-
-            if diff != latest_diff {
-                println!(
-                    "{:>14}, free {}, new diff{}: {}",
-                    format!("Block#{}", blk),
-                    new_free,
-                    sign,
-                    diff
-                );
-                latest_diff = diff;
-            } else {
-                println!(
-                    "{:>14}, free {},     diff{}: {}",
-                    format!("Block#{}", blk),
-                    new_free,
-                    sign,
-                    diff
-                );
-            }
+    let mut history = History {
+        rpc: &rpc,
+        who: who.clone(),
+        states: BTreeMap::new(),
+    };
+    let (hash, initial) = history.state_at(start_block).await?;
+    let mut records = vec![ChangeRecord::new(
+        start_block,
+        hash,
+        initial,
+        AccountState::default(),
+    )];
 
-            last_free = new_free;
-        }
+    let mut decoders = BTreeMap::new();
+    let mut last = initial;
+    for block in history.change_points(start_block, end_block).await? {
+        let (hash, state) = history.state_at(block).await?;
+        let mut record = ChangeRecord::new(block, hash, state, last);
+        let (extrinsics, events) = attribute(&rpc, &mut decoders, &who, hash).await?;
+        record.extrinsics = extrinsics;
+        record.events = events;
+        records.push(record);
+        last = state;
+    }
+
+    if app.csv {
+        print_csv(&records);
+    } else {
+        let json = serde_json::json!({
+            "who": who,
+            "startBlock": start_block,
+            "endBlock": end_block,
+            "changes": records,
+        });
+        app.output.print(&json, || print_text(&records))?;
     }
 
     Ok(())
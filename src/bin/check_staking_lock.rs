@@ -3,21 +3,17 @@
 
 use anyhow::Result;
 use chainx_cli::{
-    block_hash, build_client,
+    connection::ConnectionOptions,
     rpc::Rpc,
-    runtime::{
-        primitives::BlockNumber,
-        xpallets::xstaking::{LockedType, LocksStoreExt},
-    },
+    runtime::{primitives::BlockNumber, xpallets::xstaking::LockedType},
 };
 use structopt::StructOpt;
 
 #[derive(StructOpt, Debug)]
 #[structopt(author, about, no_version)]
 struct App {
-    /// The websocket url of ChainX node.
-    #[structopt(long, default_value = "ws://127.0.0.1:8087")]
-    pub url: String,
+    #[structopt(flatten)]
+    pub connection: ConnectionOptions,
 
     #[structopt(long)]
     pub block_number: Option<BlockNumber>,
@@ -37,21 +33,23 @@ async fn main() -> Result<()> {
 
     sp_core::crypto::set_default_ss58_version(app.ss58_prefix);
 
-    let client = build_client(app.url.clone()).await?;
-    let at = block_hash(&client, app.block_number).await?;
-
-    let rpc = Rpc::new(app.url.clone()).await?;
+    let rpc = Rpc::new(&app.connection).await?;
+    let at = match app.block_number {
+        Some(number) => rpc.block_hash(Some(number)).await?,
+        None => None,
+    };
 
     println!(
         "Running at Block #{:?}",
-        at.unwrap_or(client.block_hash(None).await?.unwrap_or_default())
+        at.unwrap_or(rpc.block_hash(None).await?.unwrap_or_default())
     );
     let accounts_info = rpc.get_accounts_info(at).await?;
+    let mut all_locks = rpc.get_locks(accounts_info.keys().cloned(), at).await?;
 
     let mut total_negative = 0;
     let mut total_unlocking = 0;
     for (who, info) in accounts_info {
-        let mut locks = client.locks(&who, at).await?;
+        let mut locks = all_locks.remove(&who).unwrap_or_default();
         let total_locked = locks.values().sum::<u128>();
         total_unlocking += *locks.entry(LockedType::BondedWithdrawal).or_default();
         let account_data = info.data;
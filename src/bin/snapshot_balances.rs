@@ -8,28 +8,20 @@ use anyhow::Result;
 use structopt::StructOpt;
 
 use sp_core::crypto::Ss58AddressFormat;
-use sp_runtime::{
-    generic::{Block, SignedBlock},
-    traits::AccountIdConversion,
-    ModuleId,
-};
-use subxt::system::System;
+use sp_runtime::{traits::AccountIdConversion, ModuleId};
 
 use chainx_cli::{
-    block_hash, build_client,
+    amount::{format_pcx, PCX},
+    connection::ConnectionOptions,
     rpc::Rpc,
-    runtime::{
-        primitives::{AccountId, Balance, BlockNumber},
-        ChainXClient, ChainXRuntime,
-    },
+    runtime::primitives::{AccountId, Balance, BlockNumber},
 };
 
 #[derive(StructOpt, Debug)]
 #[structopt(author, about, no_version)]
 struct App {
-    /// The websocket url of ChainX node.
-    #[structopt(long, default_value = "ws://127.0.0.1:8087")]
-    pub url: String,
+    #[structopt(flatten)]
+    pub connection: ConnectionOptions,
 
     /// The start block of the balance history.
     #[structopt(long)]
@@ -42,16 +34,6 @@ struct App {
     pub ss58_prefix: sp_core::crypto::Ss58AddressFormat,
 }
 
-pub type ChainBlock<T> = SignedBlock<Block<<T as System>::Header, <T as System>::Extrinsic>>;
-
-async fn latest_block_number(client: &ChainXClient) -> Result<BlockNumber> {
-    let latest_block: ChainBlock<ChainXRuntime> = client
-        .block(None::<<ChainXRuntime as System>::Hash>)
-        .await?
-        .expect("Failed to fetch the latest block");
-    Ok(latest_block.block.header.number)
-}
-
 fn save_snapshot<B, P, V>(block_number: B, prefix: P, value: &V) -> anyhow::Result<()>
 where
     B: Display,
@@ -89,22 +71,20 @@ async fn main() -> Result<()> {
 
     sp_core::crypto::set_default_ss58_version(Ss58AddressFormat::ChainXAccount);
 
-    let client = build_client(app.url.clone()).await?;
-
-    let rpc = Rpc::new(app.url).await?;
+    let rpc = Rpc::new(&app.connection).await?;
 
     let block_number = if let Some(number) = app.block_number {
         number
     } else {
-        latest_block_number(&client).await?
+        rpc.latest_block_number().await?
     };
 
-    let at = block_hash(&client, Some(block_number)).await?;
+    let at = rpc.block_hash(Some(block_number)).await?;
 
     let account_info = rpc.get_accounts_info(at).await?;
 
     /// Minimum balance to receive KSX airdrop.
-    const MINIMUM_AIRDROP_BALANCE: Balance = 100_000_000;
+    const MINIMUM_AIRDROP_BALANCE: Balance = PCX;
 
     let mut total_issuance = 0u128;
 
@@ -161,12 +141,12 @@ async fn main() -> Result<()> {
     save_snapshot(block_number, "ksx_accounts", &ksx_accounts)?;
     save_snapshot(block_number, "dust_accounts", &dust_accounts)?;
 
-    println!("       Total issuance: {}", total_issuance);
+    println!("       Total issuance: {}", format_pcx(total_issuance));
     let total_accounts = ksx_accounts.len() + dust_accounts.len();
     println!("       Total accounts: {}", total_accounts);
     println!("         KSX accounts: {}", ksx_accounts.len());
     println!("Dust accounts(< 1PCX): {}", dust_count);
-    println!("  Total dust balances: {}", dust_sum);
+    println!("  Total dust balances: {}", format_pcx(dust_sum));
 
     // Verify
     let total_ksx = ksx_accounts.iter().map(|r| r.free).sum::<Balance>();
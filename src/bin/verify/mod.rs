@@ -7,16 +7,18 @@ use codec::Encode;
 use frame_support::parameter_types;
 use sp_core::crypto::{set_default_ss58_version, Ss58AddressFormat, UncheckedFrom};
 use sp_runtime::{
-    traits::{AccountIdConversion, Hash},
+    traits::{AccountIdConversion, Hash as _},
     ModuleId,
 };
 use structopt::StructOpt;
 use subxt::system::System;
 
 use chainx_cli::{
+    amount::PCX,
+    connection::ConnectionOptions,
     rpc::Rpc,
     runtime::{
-        primitives::{AccountId, AssetId, Balance, BlockNumber},
+        primitives::{AccountId, AssetId, Balance, BlockNumber, Hash},
         xpallets::xassets::AssetType,
         ChainXRuntime,
     },
@@ -24,12 +26,10 @@ use chainx_cli::{
 
 use self::genesis::{
     read_genesis_json, FreeBalanceInfo, Nomination, NominatorInfo, ValidatorInfo, XBtcMiner,
+    XMiningAssetParams,
 };
 use frame_support::sp_std::collections::btree_map::BTreeMap;
 
-/// The decimals of PCX.
-const PCX: u128 = 100_000_000;
-
 #[derive(StructOpt, Debug)]
 #[structopt(
     name = "chainx-verify",
@@ -41,9 +41,11 @@ pub struct Config {
     /// Specify the path of genesis json file
     #[structopt(long)]
     genesis: PathBuf,
-    /// Specify the WebSocket url of ChainX node.
-    #[structopt(long, default_value = "ws://127.0.0.1:8087")]
-    chainx_url: String,
+    /// Specify the url of ChainX node, overrides `--url`.
+    #[structopt(long)]
+    chainx_url: Option<String>,
+    #[structopt(flatten)]
+    connection: ConnectionOptions,
 }
 
 impl Config {
@@ -106,6 +108,102 @@ fn validator_reward_pot_account_for(validator: &AccountId) -> AccountId {
     UncheckedFrom::unchecked_from(<ChainXRuntime as System>::Hashing::hash(&buf[..]))
 }
 
+/// Verifies the X-BTC balances against `xassets` and `xmining_asset.xbtc_info` of the genesis.
+async fn verify_xbtc_balance(
+    rpc: &Rpc,
+    genesis_hash: Hash,
+    xassets: &[FreeBalanceInfo<AccountId, Balance>],
+    xmining_asset: &XMiningAssetParams<AccountId>,
+) -> Result<()> {
+    println!("================================================================================");
+    println!("======================= Verify X-BTC Balance ===================================");
+    println!("================================================================================");
+
+    let asset_balance = rpc.get_asset_balance(Some(genesis_hash)).await?;
+
+    let mut sum = 0;
+    let mut missing_cnt = 0;
+    println!("X-BTC account number: {}", xassets.len());
+    for FreeBalanceInfo { who, free } in xassets {
+        if let Some(asset_balance) = asset_balance.get(who) {
+            let xbtc_asset = asset_balance.get(&X_BTC).unwrap();
+            let xbtc_usable_balance = xbtc_asset
+                .get(&AssetType::Usable)
+                .cloned()
+                .unwrap_or_default();
+            assert_eq!(xbtc_usable_balance, *free);
+            sum += xbtc_usable_balance;
+        } else {
+            missing_cnt += 1;
+            println!("[ERROR] Missing X-BTC asset balance of `{}`", who);
+        }
+    }
+    println!("Missing X-BTC account count: {}", missing_cnt);
+    println!(
+        "X-BTC Usable Balance Sum (XAssets AssetBalance Storage): {}",
+        sum
+    );
+
+    let total_asset_balance = rpc.get_total_asset_balance(Some(genesis_hash)).await?;
+    let total_xbtc_balance = total_asset_balance.get(&X_BTC).unwrap();
+    let total_xbtc_usable_balance = total_xbtc_balance
+        .get(&AssetType::Usable)
+        .cloned()
+        .unwrap_or_default();
+    println!(
+        "Total X-BTC Usable Balance (XAssets TotalAssetBalance Storage): {}",
+        total_xbtc_usable_balance
+    );
+
+    assert_eq!(total_xbtc_usable_balance, xmining_asset.xbtc_info.balance);
+
+    Ok(())
+}
+
+/// Verifies the X-BTC mining weights against `xmining_asset` of the genesis.
+async fn verify_xbtc_weight(
+    rpc: &Rpc,
+    genesis_hash: Hash,
+    xmining_asset: &XMiningAssetParams<AccountId>,
+) -> Result<()> {
+    println!("================================================================================");
+    println!("======================== Verify X-BTC Weight ===================================");
+    println!("================================================================================");
+
+    let miner_ledgers = rpc.get_miner_ledgers(Some(genesis_hash)).await?;
+
+    let mut sum = 0;
+    let mut missing_cnt = 0;
+    println!("X-BTC miners number: {}", xmining_asset.xbtc_miners.len());
+    for XBtcMiner { who, weight } in &xmining_asset.xbtc_miners {
+        if let Some(miner_ledger) = miner_ledgers.get(who) {
+            let xbtc_miner_ledger = miner_ledger.get(&X_BTC).unwrap();
+            let xbtc_mining_weight = xbtc_miner_ledger.last_mining_weight;
+            assert_eq!(xbtc_mining_weight, *weight);
+            sum += xbtc_mining_weight;
+        } else {
+            missing_cnt += 1;
+            println!("[ERROR] Missing X-BTC mining weight of `{}`", who);
+        }
+    }
+    println!("Missing X-BTC miner count: {}", missing_cnt);
+    println!(
+        "X-BTC Mining Weight Sum (XMiningAsset MinerLedgers Storage): {}",
+        sum
+    );
+
+    let asset_ledgers = rpc.get_asset_ledgers(Some(genesis_hash)).await?;
+    let total_xbtc_mining_weight = asset_ledgers.get(&X_BTC).unwrap().last_total_mining_weight;
+    println!(
+        "Total X-BTC Mining Weight (XMiningAsset AssetLedgers Storage): {}",
+        total_xbtc_mining_weight
+    );
+
+    assert_eq!(total_xbtc_mining_weight, xmining_asset.xbtc_info.weight);
+
+    Ok(())
+}
+
 #[async_std::main]
 async fn main() -> Result<()> {
     env_logger::init();
@@ -116,7 +214,11 @@ async fn main() -> Result<()> {
 
     set_default_ss58_version(Ss58AddressFormat::ChainXAccount);
 
-    let rpc = Rpc::new(config.chainx_url).await?;
+    let mut connection = config.connection;
+    if let Some(url) = config.chainx_url {
+        connection.url = url;
+    }
+    let rpc = Rpc::new(&connection).await?;
 
     let genesis_hash = rpc.genesis_hash().await?;
     println!("Genesis Hash: {:?}", genesis_hash);
@@ -226,91 +328,8 @@ async fn main() -> Result<()> {
         total_pcx_balance
     );
 
-    println!("================================================================================");
-    println!("======================= Verify X-BTC Balance ===================================");
-    println!("================================================================================");
-
-    let asset_balance = rpc.get_asset_balance(Some(genesis_hash)).await?;
-
-    let mut sum = 0;
-    let mut missing_cnt = 0;
-    println!("X-BTC account number: {}", genesis.xassets.len());
-    for FreeBalanceInfo { who, free } in genesis.xassets {
-        if let Some(asset_balance) = asset_balance.get(&who) {
-            let xbtc_asset = asset_balance.get(&1).unwrap();
-            let xbtc_usable_balance = xbtc_asset
-                .get(&AssetType::Usable)
-                .cloned()
-                .unwrap_or_default();
-            assert_eq!(xbtc_usable_balance, free);
-            sum += xbtc_usable_balance;
-        } else {
-            missing_cnt += 1;
-            println!("[ERROR] Missing X-BTC asset balance of `{}`", who);
-        }
-    }
-    println!("Missing X-BTC account count: {}", missing_cnt);
-    println!(
-        "X-BTC Usable Balance Sum (XAssets AssetBalance Storage): {}",
-        sum
-    );
-
-    let total_asset_balance = rpc.get_total_asset_balance(Some(genesis_hash)).await?;
-    let total_xbtc_balance = total_asset_balance.get(&1).unwrap();
-    let total_xbtc_usable_balance = total_xbtc_balance
-        .get(&AssetType::Usable)
-        .cloned()
-        .unwrap_or_default();
-    println!(
-        "Total X-BTC Usable Balance (XAssets TotalAssetBalance Storage): {}",
-        total_xbtc_usable_balance
-    );
-
-    assert_eq!(
-        total_xbtc_usable_balance,
-        genesis.xmining_asset.xbtc_info.balance
-    );
-
-    println!("================================================================================");
-    println!("======================== Verify X-BTC Weight ===================================");
-    println!("================================================================================");
-
-    let miner_ledgers = rpc.get_miner_ledgers(Some(genesis_hash)).await?;
-
-    let mut sum = 0;
-    let mut missing_cnt = 0;
-    println!(
-        "X-BTC miners number: {}",
-        genesis.xmining_asset.xbtc_miners.len()
-    );
-    for XBtcMiner { who, weight } in genesis.xmining_asset.xbtc_miners {
-        if let Some(miner_ledger) = miner_ledgers.get(&who) {
-            let xbtc_miner_ledger = miner_ledger.get(&1).unwrap();
-            let xbtc_mining_weight = xbtc_miner_ledger.last_mining_weight;
-            assert_eq!(xbtc_mining_weight, weight);
-            sum += xbtc_mining_weight;
-        } else {
-            missing_cnt += 1;
-            println!("[ERROR] Missing X-BTC mining weight of `{}`", who);
-        }
-    }
-    println!("Missing X-BTC miner count: {}", missing_cnt);
-    println!(
-        "X-BTC Mining Weight Sum (XMiningAsset MinerLedgers Storage): {}",
-        sum
-    );
-
-    let asset_ledgers = rpc.get_asset_ledgers(Some(genesis_hash)).await?;
-    let total_xbtc_mining_weight = asset_ledgers.get(&1).unwrap().last_total_mining_weight;
-    println!(
-        "Total X-BTC Mining Weight (XMiningAsset AssetLedgers Storage): {}",
-        total_xbtc_mining_weight
-    );
-
-    assert_eq!(
-        total_xbtc_mining_weight,
-        genesis.xmining_asset.xbtc_info.weight
-    );
+    verify_xbtc_balance(&rpc, genesis_hash, &genesis.xassets, &genesis.xmining_asset).await?;
+    verify_xbtc_weight(&rpc, genesis_hash, &genesis.xmining_asset).await?;
 
     println!("================================================================================");
     println!("====================== Verify Vote Nomination & Weight =========================");
@@ -395,3 +414,85 @@ async fn main() -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use chainx_cli::{
+        rpc::{ASSET_BALANCE, ASSET_LEDGERS, MINER_LEDGERS, TOTAL_ASSET_BALANCE},
+        runtime::xpallets::xmining_asset::{AssetLedger, MinerLedger},
+        state::StateFile,
+    };
+
+    use super::genesis::XBtcInfo;
+    use super::*;
+
+    fn xbtc_rpc(who: &AccountId, balance: Balance, weight: u128) -> Rpc {
+        let usable = vec![(AssetType::Usable, balance)]
+            .into_iter()
+            .collect::<BTreeMap<_, _>>();
+        let miner_ledger = MinerLedger::<u128, BlockNumber> {
+            last_mining_weight: weight,
+            ..Default::default()
+        };
+        let asset_ledger = AssetLedger::<u128, BlockNumber> {
+            last_total_mining_weight: weight,
+            ..Default::default()
+        };
+        let storage = vec![
+            (ASSET_BALANCE.key(who, &X_BTC), usable.encode()),
+            (TOTAL_ASSET_BALANCE.key(&X_BTC), usable.encode()),
+            (MINER_LEDGERS.key(who, &X_BTC), miner_ledger.encode()),
+            (ASSET_LEDGERS.key(&X_BTC), asset_ledger.encode()),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.0, value))
+        .collect();
+        let backend = Arc::new(StateFile::genesis(storage));
+        Rpc::with_backend(backend, &ConnectionOptions::new("ws://127.0.0.1:8087")).unwrap()
+    }
+
+    fn xmining_asset(
+        who: &AccountId,
+        balance: Balance,
+        weight: u128,
+    ) -> XMiningAssetParams<AccountId> {
+        XMiningAssetParams {
+            xbtc_miners: vec![XBtcMiner {
+                who: who.clone(),
+                weight,
+            }],
+            xbtc_info: XBtcInfo { balance, weight },
+        }
+    }
+
+    #[async_std::test]
+    async fn test_verify_xbtc() {
+        let who = AccountId::from([1u8; 32]);
+        let rpc = xbtc_rpc(&who, 100, 2000);
+        let genesis_hash = rpc.genesis_hash().await.unwrap();
+        let xassets = vec![FreeBalanceInfo {
+            who: who.clone(),
+            free: 100,
+        }];
+        let xmining_asset = xmining_asset(&who, 100, 2000);
+
+        verify_xbtc_balance(&rpc, genesis_hash, &xassets, &xmining_asset)
+            .await
+            .unwrap();
+        verify_xbtc_weight(&rpc, genesis_hash, &xmining_asset)
+            .await
+            .unwrap();
+    }
+
+    #[async_std::test]
+    #[should_panic]
+    async fn test_verify_xbtc_weight_mismatch() {
+        let who = AccountId::from([1u8; 32]);
+        let rpc = xbtc_rpc(&who, 100, 2000);
+        let genesis_hash = rpc.genesis_hash().await.unwrap();
+
+        let _ = verify_xbtc_weight(&rpc, genesis_hash, &xmining_asset(&who, 100, 1000)).await;
+    }
+}
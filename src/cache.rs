@@ -0,0 +1,234 @@
+//! On-disk cache of the storage map entries.
+//!
+//! The entries under a storage key prefix never change at a given block, they are cached as
+//! `<dir>/<block hash>/<prefix>.scale`, the SCALE encoded `Vec<(key, value)>`.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Result};
+use codec::{Decode, Encode};
+use serde::Serialize;
+use sp_core::storage::{StorageData, StorageKey};
+
+use crate::runtime::primitives::Hash;
+
+const EXTENSION: &str = "scale";
+
+/// A cached storage key prefix.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheEntry {
+    pub block: String,
+    pub prefix: String,
+    pub size: u64,
+}
+
+/// Cache of the storage entries, keyed by `(block hash, storage key prefix)`.
+#[derive(Clone, Debug)]
+pub struct StorageCache {
+    path: PathBuf,
+}
+
+impl StorageCache {
+    /// Returns the default directory of the cache.
+    pub fn default_path() -> Result<PathBuf> {
+        let mut path = dirs::cache_dir().ok_or_else(|| anyhow!("Can not find the cache dir"))?;
+        path.push("chainx-cli");
+        path.push("storage");
+        Ok(path)
+    }
+
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Returns the directory of the cache.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn block_dir(&self, hash: Hash) -> PathBuf {
+        self.path.join(format!("{:?}", hash))
+    }
+
+    fn file(&self, hash: Hash, prefix: &StorageKey) -> PathBuf {
+        self.block_dir(hash)
+            .join(format!("0x{}.{}", hex::encode(&prefix.0), EXTENSION))
+    }
+
+    /// Returns the cached entries under `prefix` at block `hash`.
+    pub fn get(
+        &self,
+        hash: Hash,
+        prefix: &StorageKey,
+    ) -> Result<Option<Vec<(StorageKey, StorageData)>>> {
+        let file = self.file(hash, prefix);
+        if !file.is_file() {
+            return Ok(None);
+        }
+        let data = fs::read(&file)?;
+        let pairs = <Vec<(Vec<u8>, Vec<u8>)>>::decode(&mut data.as_slice())
+            .map_err(|err| anyhow!("Corrupted cache file {}: {}", file.display(), err))?;
+        Ok(Some(
+            pairs
+                .into_iter()
+                .map(|(key, value)| (StorageKey(key), StorageData(value)))
+                .collect(),
+        ))
+    }
+
+    /// Caches the entries under `prefix` at block `hash`.
+    pub fn put(
+        &self,
+        hash: Hash,
+        prefix: &StorageKey,
+        pairs: &[(StorageKey, StorageData)],
+    ) -> Result<()> {
+        let file = self.file(hash, prefix);
+        if let Some(dir) = file.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let data = pairs
+            .iter()
+            .map(|(key, value)| (&key.0, &value.0))
+            .collect::<Vec<_>>()
+            .encode();
+        // Write to a temporary file first, an interrupted write never leaves a corrupted entry.
+        let tmp = file.with_extension("tmp");
+        fs::write(&tmp, data)?;
+        fs::rename(&tmp, &file)?;
+        Ok(())
+    }
+
+    /// Returns all the cached entries.
+    ///
+    /// Only the `0x<prefix>.scale` files under the `0x<block hash>` directories are listed, the
+    /// other files in the directory are never touched.
+    pub fn entries(&self) -> Result<Vec<CacheEntry>> {
+        Ok(self
+            .entry_files()?
+            .into_iter()
+            .map(|(entry, _)| entry)
+            .collect())
+    }
+
+    fn entry_files(&self) -> Result<Vec<(CacheEntry, PathBuf)>> {
+        let mut entries = Vec::new();
+        if !self.path.is_dir() {
+            return Ok(entries);
+        }
+        for block in fs::read_dir(&self.path)? {
+            let block = block?;
+            let name = block.file_name().to_string_lossy().into_owned();
+            if !block.file_type()?.is_dir() || parse_hash(&name).is_none() {
+                continue;
+            }
+            for file in fs::read_dir(block.path())? {
+                let path = file?.path();
+                if path.extension().and_then(|ext| ext.to_str()) != Some(EXTENSION) {
+                    continue;
+                }
+                let prefix = match path.file_stem().and_then(|stem| stem.to_str()) {
+                    Some(stem) if is_hex(stem) => stem.to_string(),
+                    _ => continue,
+                };
+                let entry = CacheEntry {
+                    block: name.clone(),
+                    prefix,
+                    size: fs::metadata(&path)?.len(),
+                };
+                entries.push((entry, path));
+            }
+        }
+        entries.sort_by(|(a, _), (b, _)| (&a.block, &a.prefix).cmp(&(&b.block, &b.prefix)));
+        Ok(entries)
+    }
+
+    /// Removes the cached entries of `block`, or all of them if `block` is `None`.
+    ///
+    /// The emptied block directories and cache directory are removed as well. Returns the number
+    /// of the removed entries.
+    pub fn purge(&self, block: Option<Hash>) -> Result<usize> {
+        let block = block.map(|hash| format!("{:?}", hash));
+        let mut removed = 0;
+        for (entry, path) in self.entry_files()? {
+            if block.is_some() && block.as_ref() != Some(&entry.block) {
+                continue;
+            }
+            fs::remove_file(&path)?;
+            removed += 1;
+            if let Some(dir) = path.parent() {
+                remove_dir_if_empty(dir)?;
+            }
+        }
+        if block.is_none() {
+            remove_dir_if_empty(&self.path)?;
+        }
+        Ok(removed)
+    }
+}
+
+/// Parses the `0x`-prefixed lowercase block hash of a cache directory.
+fn parse_hash(name: &str) -> Option<Hash> {
+    if is_hex(name) {
+        name[2..].parse().ok()
+    } else {
+        None
+    }
+}
+
+/// Whether `name` is the `0x`-prefixed lowercase hex written by the cache.
+fn is_hex(name: &str) -> bool {
+    name.starts_with("0x")
+        && name[2..]
+            .chars()
+            .all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c))
+}
+
+fn remove_dir_if_empty(dir: &Path) -> Result<()> {
+    if dir.is_dir() && fs::read_dir(dir)?.next().is_none() {
+        fs::remove_dir(dir)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_storage_cache() {
+        let dir = std::env::temp_dir().join(format!("chainx-cli-cache-{}", rand::random::<u64>()));
+        let cache = StorageCache::new(dir.clone());
+        let hash = Hash::repeat_byte(1);
+        let prefix = StorageKey(vec![1, 2, 3]);
+        let pairs = vec![(StorageKey(vec![1, 2, 3, 4]), StorageData(vec![5, 6]))];
+
+        assert!(cache.get(hash, &prefix).unwrap().is_none());
+        cache.put(hash, &prefix, &pairs).unwrap();
+        assert_eq!(cache.get(hash, &prefix).unwrap(), Some(pairs.clone()));
+        assert!(cache.get(Hash::repeat_byte(2), &prefix).unwrap().is_none());
+
+        let entries = cache.entries().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].prefix, "0x010203");
+
+        let other = dir.join("other");
+        fs::write(&other, b"not cached").unwrap();
+        assert_eq!(cache.purge(Some(Hash::repeat_byte(2))).unwrap(), 0);
+        assert_eq!(cache.purge(Some(hash)).unwrap(), 1);
+        assert!(cache.entries().unwrap().is_empty());
+        assert!(!cache.block_dir(hash).exists());
+
+        cache.put(hash, &prefix, &pairs).unwrap();
+        assert_eq!(cache.purge(None).unwrap(), 1);
+        // The files not created by the cache are kept.
+        assert!(other.exists());
+        fs::remove_file(&other).unwrap();
+        assert_eq!(cache.purge(None).unwrap(), 0);
+        assert!(!dir.exists());
+    }
+}
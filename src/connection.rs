@@ -0,0 +1,169 @@
+//! Connection to the ChainX node.
+
+use std::{fmt, future::Future, path::PathBuf, time::Duration};
+
+use anyhow::{anyhow, Result};
+use jsonrpsee::Client;
+use structopt::StructOpt;
+
+use crate::cache::StorageCache;
+
+/// The longest delay between two retries.
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Transport of the JSON-RPC requests.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Transport {
+    /// `ws://` or `wss://`, supports the subscriptions.
+    Ws,
+    /// `http://` or `https://`.
+    Http,
+}
+
+/// A failure of the connection or the transport, unlike the error responses of the node the
+/// request may succeed if retried.
+#[derive(Debug)]
+pub struct TransportError(pub String);
+
+impl fmt::Display for TransportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for TransportError {}
+
+/// Where and how to connect to the ChainX node.
+#[derive(Clone, Debug, StructOpt)]
+pub struct ConnectionOptions {
+    /// The url of ChainX node.
+    ///
+    /// The transport is chosen by the scheme, `ws://` and `wss://` for the websocket,
+    /// `http://` and `https://` for HTTP. Extrinsics can't be watched over HTTP, use
+    /// `--wait none` with an HTTP url.
+    #[structopt(long, default_value = "ws://127.0.0.1:8087")]
+    pub url: String,
+
+    /// How many times a failed connection or read-only request is retried.
+    ///
+    /// The connection is re-established before each retry of a request.
+    #[structopt(long, default_value = "5")]
+    pub max_retries: u32,
+
+    /// Timeout of each request in seconds.
+    #[structopt(long, default_value = "30")]
+    pub request_timeout: u64,
+
+    /// Delay before the first retry in milliseconds, doubled for each next retry up to 60s.
+    #[structopt(long, default_value = "500")]
+    pub retry_backoff: u64,
+
+    /// Cache the storage map entries fetched at a block on disk, and reuse them later.
+    ///
+    /// Use `chainx-cli cache` to list or purge the cache.
+    #[structopt(long)]
+    pub cache: bool,
+
+    /// Directory of the storage cache.
+    ///
+    /// Default is `<cache_dir>/chainx-cli/storage`.
+    #[structopt(long, parse(from_os_str))]
+    pub cache_dir: Option<PathBuf>,
+
+    /// Read the storage from the state file dumped by `chainx-cli state dump` instead of
+    /// the node.
+    ///
+    /// Only the block and storage queries are supported offline.
+    #[structopt(long, parse(from_os_str))]
+    pub state: Option<PathBuf>,
+}
+
+impl ConnectionOptions {
+    /// Returns the default options for `url`, i.e., the command line defaults.
+    pub fn new<U: Into<String>>(url: U) -> Self {
+        let url = url.into();
+        Self::from_iter(&["chainx-cli", "--url", &url])
+    }
+
+    /// Returns the directory of the storage cache.
+    pub fn cache_path(&self) -> Result<PathBuf> {
+        match self.cache_dir {
+            Some(ref path) => Ok(path.clone()),
+            None => StorageCache::default_path(),
+        }
+    }
+
+    /// Returns the storage cache if enabled.
+    pub fn storage_cache(&self) -> Result<Option<StorageCache>> {
+        if self.cache {
+            Ok(Some(StorageCache::new(self.cache_path()?)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Returns the transport of the url scheme.
+    pub fn transport(&self) -> Result<Transport> {
+        let scheme = self.url.split("://").next().unwrap_or_default();
+        match scheme.to_ascii_lowercase().as_str() {
+            "ws" | "wss" => Ok(Transport::Ws),
+            "http" | "https" => Ok(Transport::Http),
+            _ => Err(anyhow!(
+                "Unsupported url `{}`, expected ws://, wss://, http:// or https://",
+                self.url
+            )),
+        }
+    }
+
+    /// Creates the JSON-RPC client of the url transport, without retrying.
+    pub async fn rpc_client(&self) -> Result<Client> {
+        match self.transport()? {
+            Transport::Ws => Ok(jsonrpsee::ws_client(&self.url).await?),
+            Transport::Http => Ok(jsonrpsee::http_client(&self.url)),
+        }
+    }
+
+    pub fn request_timeout(&self) -> Duration {
+        Duration::from_secs(self.request_timeout)
+    }
+
+    /// Returns the delay before the retry `attempt`, starting from 0.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let backoff = Duration::from_millis(self.retry_backoff);
+        backoff
+            .checked_mul(2u32.saturating_pow(attempt))
+            .map_or(MAX_BACKOFF, |backoff| backoff.min(MAX_BACKOFF))
+    }
+
+    /// Calls `connect` until it succeeds within the request timeout or runs out of retries.
+    pub async fn connect<T, F, Fut>(&self, mut connect: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut attempt = 0;
+        loop {
+            let result = match async_std::future::timeout(self.request_timeout(), connect()).await {
+                Ok(result) => result,
+                Err(_) => Err(anyhow!("Timed out after {:?}", self.request_timeout())),
+            };
+            match result {
+                Ok(connection) => return Ok(connection),
+                Err(err) if attempt < self.max_retries => {
+                    let backoff = self.backoff(attempt);
+                    log::warn!(
+                        "Failed to connect to {}: {}, retrying in {:?}",
+                        self.url,
+                        err,
+                        backoff
+                    );
+                    async_std::task::sleep(backoff).await;
+                    attempt += 1;
+                }
+                Err(err) => {
+                    return Err(err.context(format!("Failed to connect to {}", self.url)));
+                }
+            }
+        }
+    }
+}
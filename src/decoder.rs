@@ -0,0 +1,868 @@
+//! Decodes the events and the extrinsics with the runtime metadata.
+//!
+//! The arguments are decoded by their type names in the metadata. The length of a value of an
+//! unknown type is unknown too, so the bytes from such an argument on are kept as raw hex, up to
+//! the following events if they can be found.
+
+use std::{collections::BTreeMap, fmt};
+
+use anyhow::{anyhow, Result};
+use codec::{Compact, Decode};
+use frame_metadata::{decode_different::DecodeDifferent, RuntimeMetadata, RuntimeMetadataPrefixed};
+use frame_support::{traits::BalanceStatus, weights::DispatchInfo};
+use serde::Serialize;
+use serde_json::{json, Value as JsonValue};
+use sp_runtime::{generic::Era, DispatchError, MultiSignature};
+use subxt::system::Phase;
+
+use crate::{
+    output::NumStr,
+    runtime::{
+        primitives::{AccountId, Address, Balance, Hash, Index},
+        xpallets::{xassets::AssetType, xstaking::LockedType},
+    },
+};
+
+/// Version of the extrinsic format.
+const EXTRINSIC_VERSION: u8 = 4;
+
+/// An event of a module in the metadata.
+#[derive(Clone, Debug)]
+pub struct EventMeta {
+    pub name: String,
+    /// Type names of the arguments.
+    pub arguments: Vec<String>,
+}
+
+/// A call of a module in the metadata.
+#[derive(Clone, Debug)]
+pub struct CallMeta {
+    pub name: String,
+    /// Names and type names of the arguments.
+    pub arguments: Vec<(String, String)>,
+}
+
+/// A module in the metadata.
+#[derive(Clone, Debug, Default)]
+pub struct ModuleMeta {
+    pub name: String,
+    /// Indexed by the call index.
+    pub calls: Vec<CallMeta>,
+    /// Indexed by the event index.
+    pub events: Vec<EventMeta>,
+    /// Indexed by the error index.
+    pub errors: Vec<String>,
+}
+
+/// Decoder of the events and extrinsics of a runtime version.
+#[derive(Clone, Debug)]
+pub struct Decoder {
+    /// Indexed by the module index.
+    modules: BTreeMap<u8, ModuleMeta>,
+}
+
+fn decoded<B: 'static, O: 'static + Clone>(value: &DecodeDifferent<B, O>) -> Result<O> {
+    match value {
+        DecodeDifferent::Decoded(value) => Ok(value.clone()),
+        DecodeDifferent::Encode(_) => Err(anyhow!("Metadata should be Decoded")),
+    }
+}
+
+/// Collects the modules of the V12 and V13 metadata, which share the layout of the modules.
+macro_rules! modules_of {
+    ($metadata:expr) => {{
+        let mut modules = BTreeMap::new();
+        for module in decoded(&$metadata.modules)? {
+            let calls = match module.calls {
+                Some(ref calls) => decoded(calls)?
+                    .iter()
+                    .map(|call| {
+                        let arguments = decoded(&call.arguments)?
+                            .iter()
+                            .map(|arg| Ok((decoded(&arg.name)?, decoded(&arg.ty)?)))
+                            .collect::<Result<Vec<_>>>()?;
+                        Ok(CallMeta {
+                            name: decoded(&call.name)?,
+                            arguments,
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()?,
+                None => Vec::new(),
+            };
+            let events = match module.event {
+                Some(ref events) => decoded(events)?
+                    .iter()
+                    .map(|event| {
+                        Ok(EventMeta {
+                            name: decoded(&event.name)?,
+                            arguments: decoded(&event.arguments)?,
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()?,
+                None => Vec::new(),
+            };
+            let errors = decoded(&module.errors)?
+                .iter()
+                .map(|error| decoded(&error.name))
+                .collect::<Result<Vec<_>>>()?;
+            let name = decoded(&module.name)?;
+            modules.insert(
+                module.index,
+                ModuleMeta {
+                    name,
+                    calls,
+                    events,
+                    errors,
+                },
+            );
+        }
+        modules
+    }};
+}
+
+impl Decoder {
+    /// Creates the decoder from the SCALE encoded metadata.
+    pub fn new(metadata: &[u8]) -> Result<Self> {
+        let metadata: RuntimeMetadataPrefixed = scale::Decode::decode(&mut &metadata[..])?;
+        let modules = match metadata.1 {
+            RuntimeMetadata::V12(v12) => modules_of!(v12),
+            RuntimeMetadata::V13(v13) => modules_of!(v13),
+            _ => return Err(anyhow!("Unsupported metadata version")),
+        };
+        Ok(Self { modules })
+    }
+
+    /// Decodes the SCALE encoded `System::Events`.
+    ///
+    /// The events following one with an argument of an unknown type are found by the first offset
+    /// from which they all decode to the end of the data. The skipped bytes are in the `raw` of
+    /// the event, or the decoding stops at that event if there is no such offset.
+    pub fn decode_events(&self, data: &[u8]) -> Result<Vec<(Phase, DecodedEvent)>> {
+        let input = &mut &data[..];
+        let count = <Compact<u32>>::decode(input)?.0;
+        let mut events = Vec::new();
+        for decoded in 0..count {
+            let (phase, mut event) = self.decode_event(input)?;
+            if event.raw.is_none() {
+                decode_topics(input)?;
+                events.push((phase, event));
+                continue;
+            }
+
+            match self.resync(*input, count - decoded - 1) {
+                Some((skipped, following)) => {
+                    event.raw = Some(format!("0x{}", hex::encode(skipped)));
+                    events.push((phase, event));
+                    events.extend(following);
+                }
+                None => events.push((phase, event)),
+            }
+            break;
+        }
+        Ok(events)
+    }
+
+    /// Decodes the phase and the event of an event record, without the topics.
+    ///
+    /// The bytes from the first argument of an unknown type on are kept in `raw`.
+    fn decode_event(&self, input: &mut &[u8]) -> Result<(Phase, DecodedEvent)> {
+        let phase = Phase::decode(input)?;
+        let (module_index, event_index) = (u8::decode(input)?, u8::decode(input)?);
+        let meta = self.modules.get(&module_index).and_then(|module| {
+            module
+                .events
+                .get(event_index as usize)
+                .map(|event| (module, event))
+        });
+        let (module, meta) = match meta {
+            Some(meta) => meta,
+            None => {
+                let event = DecodedEvent {
+                    pallet: format!("Unknown({})", module_index),
+                    event: format!("Unknown({})", event_index),
+                    args: Vec::new(),
+                    raw: Some(format!("0x{}", hex::encode(*input))),
+                };
+                return Ok((phase, event));
+            }
+        };
+
+        let mut event = DecodedEvent {
+            pallet: module.name.clone(),
+            event: meta.name.clone(),
+            args: Vec::with_capacity(meta.arguments.len()),
+            raw: None,
+        };
+        for ty in &meta.arguments {
+            match self.decode_value(ty, input)? {
+                Some(value) => event.args.push(value),
+                None => {
+                    event.raw = Some(format!("0x{}", hex::encode(*input)));
+                    break;
+                }
+            }
+        }
+        Ok((phase, event))
+    }
+
+    /// Returns the bytes skipped before the `count` events decoded exactly to the end of `input`,
+    /// and the events.
+    fn resync<'a>(
+        &self,
+        input: &'a [u8],
+        count: u32,
+    ) -> Option<(&'a [u8], Vec<(Phase, DecodedEvent)>)> {
+        (0..=input.len()).find_map(|offset| {
+            let rest = &mut &input[offset..];
+            let mut events = Vec::new();
+            for _ in 0..count {
+                match self.decode_event(rest) {
+                    Ok((phase, event)) if event.raw.is_none() && decode_topics(rest).is_ok() => {
+                        events.push((phase, event))
+                    }
+                    _ => return None,
+                }
+            }
+            if rest.is_empty() {
+                Some((&input[..offset], events))
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Decodes the call, the arguments from the first one of an unknown type on are kept in
+    /// `raw`.
+    pub fn decode_call(&self, input: &mut &[u8]) -> Result<DecodedCall> {
+        let (module_index, call_index) = (u8::decode(input)?, u8::decode(input)?);
+        let meta = self.modules.get(&module_index).and_then(|module| {
+            module
+                .calls
+                .get(call_index as usize)
+                .map(|call| (module, call))
+        });
+        let (module, meta) = match meta {
+            Some(meta) => meta,
+            None => {
+                return Ok(DecodedCall {
+                    pallet: format!("Unknown({})", module_index),
+                    call: format!("Unknown({})", call_index),
+                    args: Vec::new(),
+                    raw: Some(format!("0x{}", hex::encode(*input))),
+                })
+            }
+        };
+
+        let mut call = DecodedCall {
+            pallet: module.name.clone(),
+            call: meta.name.clone(),
+            args: Vec::with_capacity(meta.arguments.len()),
+            raw: None,
+        };
+        for (name, ty) in &meta.arguments {
+            match self.decode_value(ty, input)? {
+                Some(value) => call.args.push(CallArg {
+                    name: name.clone(),
+                    value,
+                }),
+                None => {
+                    call.raw = Some(format!("0x{}", hex::encode(*input)));
+                    break;
+                }
+            }
+        }
+        Ok(call)
+    }
+
+    /// Decodes the SCALE encoded extrinsic signed with `ChainXExtra`, or an unsigned one.
+    pub fn decode_extrinsic(&self, extrinsic: &[u8]) -> Result<DecodedExtrinsic> {
+        let hash = Hash::from(sp_core::blake2_256(extrinsic));
+        let data = <Vec<u8>>::decode(&mut &extrinsic[..])?;
+        let input = &mut &data[..];
+
+        let version = u8::decode(input)?;
+        if version & 0b0111_1111 != EXTRINSIC_VERSION {
+            return Err(anyhow!(
+                "Unsupported extrinsic version {}",
+                version & 0b0111_1111
+            ));
+        }
+        let signature = if version & 0b1000_0000 != 0 {
+            let signer = Address::decode(input)?;
+            let _signature = MultiSignature::decode(input)?;
+            // The signed extra of `ChainXExtra`, the other extensions encode nothing.
+            let era = Era::decode(input)?;
+            let nonce = <Compact<Index>>::decode(input)?.0;
+            let tip = <Compact<Balance>>::decode(input)?.0;
+            Some(ExtrinsicSignature {
+                signer: format_address(&signer),
+                nonce,
+                tip: NumStr(tip),
+                era: format_era(era),
+            })
+        } else {
+            None
+        };
+
+        Ok(DecodedExtrinsic {
+            hash,
+            signature,
+            call: self.decode_call(input)?,
+        })
+    }
+
+    /// Returns `Pallet.Error` of the module errors, the debug format of the others.
+    fn dispatch_error(&self, error: DispatchError) -> String {
+        if let DispatchError::Module { index, error, .. } = error {
+            if let Some(module) = self.modules.get(&index) {
+                if let Some(name) = module.errors.get(error as usize) {
+                    return format!("{}.{}", module.name, name);
+                }
+            }
+        }
+        format!("{:?}", error)
+    }
+
+    /// Decodes a value of the type named `ty` in the metadata to JSON.
+    ///
+    /// Returns `None` and leaves `input` unchanged if the type is unknown. The balances are strings
+    /// like the other JSON outputs.
+    pub fn decode_value(&self, ty: &str, input: &mut &[u8]) -> Result<Option<JsonValue>> {
+        let start = *input;
+        let value = self.decode_known_value(&normalize_type(ty), input)?;
+        if value.is_none() {
+            *input = start;
+        }
+        Ok(value)
+    }
+
+    fn decode_known_value(&self, ty: &str, input: &mut &[u8]) -> Result<Option<JsonValue>> {
+        if ty == "Vec<u8>" || ty == "Bytes" {
+            return Ok(Some(json!(format!(
+                "0x{}",
+                hex::encode(decode_bytes(input)?)
+            ))));
+        }
+        if let Some(inner) = generic_arg(ty, "Vec") {
+            let len = <Compact<u32>>::decode(input)?.0;
+            // The length may be garbage while resyncing the events, don't trust it for capacity.
+            let mut values = Vec::with_capacity((len as usize).min(input.len()));
+            for _ in 0..len {
+                match self.decode_value(inner, input)? {
+                    Some(value) => values.push(value),
+                    None => return Ok(None),
+                }
+            }
+            return Ok(Some(JsonValue::Array(values)));
+        }
+        if let Some(inner) = generic_arg(ty, "Box") {
+            return self.decode_value(inner, input);
+        }
+        if let Some(inner) = generic_arg(ty, "Option") {
+            return match u8::decode(input)? {
+                0 => Ok(Some(JsonValue::Null)),
+                1 => self.decode_value(inner, input),
+                _ => Err(anyhow!("Invalid Option of `{}`", ty)),
+            };
+        }
+        if let Some(inner) = generic_arg(ty, "Compact") {
+            let value = <Compact<u128>>::decode(input)?.0;
+            return match normalize_type(inner).as_str() {
+                "u8" | "u16" | "u32" | "u64" | "AssetId" | "BlockNumber" | "Index" | "Moment"
+                | "Weight" => Ok(Some(json!(value))),
+                _ => Ok(Some(json!(value.to_string()))),
+            };
+        }
+        if let Some(inner) = generic_arg(ty, "Timepoint") {
+            let height = match self.decode_value(inner, input)? {
+                Some(height) => height,
+                None => return Ok(None),
+            };
+            let index = u32::decode(input)?;
+            return Ok(Some(json!({ "height": height, "index": index })));
+        }
+        if ty.starts_with('(') && ty.ends_with(')') {
+            let mut values = Vec::new();
+            for inner in split_tuple(&ty[1..ty.len() - 1]) {
+                match self.decode_value(inner, input)? {
+                    Some(value) => values.push(value),
+                    None => return Ok(None),
+                }
+            }
+            return Ok(Some(JsonValue::Array(values)));
+        }
+        if ty.starts_with("[u8;") && ty.ends_with(']') {
+            let len = match ty[4..ty.len() - 1].parse::<usize>() {
+                Ok(len) => len,
+                Err(_) => return Ok(None),
+            };
+            return Ok(Some(json!(format!("0x{}", hex::encode(take(input, len)?)))));
+        }
+
+        let value = match ty {
+            "bool" => json!(bool::decode(input)?),
+            "u8" => json!(u8::decode(input)?),
+            "u16" => json!(u16::decode(input)?),
+            "u32" | "AssetId" | "BlockNumber" | "SessionIndex" | "EraIndex" | "AccountIndex"
+            | "Index" | "AuthorityIndex" | "ProposalIndex" | "MemberCount" | "UnbondedIndex"
+            | "TradingPairId" | "WithdrawalRecordId" => json!(u32::decode(input)?),
+            "u64" | "Moment" | "Weight" | "AuthorityWeight" | "OrderId" => {
+                json!(u64::decode(input)?)
+            }
+            "u128" | "Balance" | "BalanceOf" | "MiningWeight" | "VoteWeight" | "Price" => {
+                json!(u128::decode(input)?.to_string())
+            }
+            "AccountId" => json!(AccountId::decode(input)?.to_string()),
+            "Hash" | "H256" | "BlockHash" | "CallHash" => {
+                json!(format!("{:?}", Hash::decode(input)?))
+            }
+            "AuthorityId" => json!(format!("0x{}", hex::encode(take(input, 32)?))),
+            "AuthorityList" => {
+                return self.decode_value("Vec<(AuthorityId, AuthorityWeight)>", input);
+            }
+            "ReferralId" | "Memo" | "Text" => {
+                json!(String::from_utf8_lossy(decode_bytes(input)?).into_owned())
+            }
+            "Kind" => json!(format!("0x{}", hex::encode(take(input, 16)?))),
+            "OpaqueTimeSlot" => json!(format!("0x{}", hex::encode(decode_bytes(input)?))),
+            "Phase" => json!(format!("{:?}", Phase::decode(input)?)),
+            "AssetType" => json!(format!("{:?}", AssetType::decode(input)?)),
+            "LockedType" => json!(format!("{:?}", LockedType::decode(input)?)),
+            "Status" | "BalanceStatus" => json!(format!("{:?}", BalanceStatus::decode(input)?)),
+            "Chain" => decode_variant(ty, &["ChainX", "Bitcoin", "Ethereum", "Polkadot"], input)?,
+            "NetworkType" => decode_variant(ty, &["Mainnet", "Testnet"], input)?,
+            "Side" => decode_variant(ty, &["Buy", "Sell"], input)?,
+            "WithdrawalState" => decode_variant(
+                ty,
+                &[
+                    "Applying",
+                    "Processing",
+                    "NormalFinish",
+                    "RootFinish",
+                    "NormalCancel",
+                    "RootCancel",
+                ],
+                input,
+            )?,
+            "DispatchInfo" => {
+                let info = DispatchInfo::decode(input)?;
+                json!({
+                    "weight": info.weight,
+                    "class": format!("{:?}", info.class),
+                    "paysFee": format!("{:?}", info.pays_fee),
+                })
+            }
+            "DispatchError" => json!(self.dispatch_error(DispatchError::decode(input)?)),
+            "DispatchResult" => match <Result<(), DispatchError>>::decode(input)? {
+                Ok(()) => json!("Ok"),
+                Err(error) => json!({ "Err": self.dispatch_error(error) }),
+            },
+            "Source" | "LookupSource" | "Address" => {
+                json!(format_address(&Address::decode(input)?))
+            }
+            "Call" => match self.decode_call(input)? {
+                call if call.raw.is_none() => serde_json::to_value(call)?,
+                _ => return Ok(None),
+            },
+            _ => return Ok(None),
+        };
+        Ok(Some(value))
+    }
+}
+
+/// An event decoded with the metadata.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodedEvent {
+    pub pallet: String,
+    pub event: String,
+    /// The decoded arguments, up to the first one of an unknown type.
+    pub args: Vec<JsonValue>,
+    /// Hex of the undecoded bytes from the first argument of an unknown type, including the
+    /// topics, and the following events if they can't be found.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub raw: Option<String>,
+}
+
+impl fmt::Display for DecodedEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let args = self.args.iter().map(format_json).collect::<Vec<_>>();
+        write!(f, "{}.{}({})", self.pallet, self.event, args.join(", "))?;
+        if let Some(ref raw) = self.raw {
+            write!(f, " undecoded: {}", raw)?;
+        }
+        Ok(())
+    }
+}
+
+/// A named argument of a call.
+#[derive(Clone, Debug, Serialize)]
+pub struct CallArg {
+    pub name: String,
+    pub value: JsonValue,
+}
+
+/// A call decoded with the metadata.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodedCall {
+    pub pallet: String,
+    pub call: String,
+    /// The decoded arguments, up to the first one of an unknown type.
+    pub args: Vec<CallArg>,
+    /// Hex of the undecoded arguments from the first one of an unknown type.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub raw: Option<String>,
+}
+
+impl fmt::Display for DecodedCall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let args = self
+            .args
+            .iter()
+            .map(|arg| format!("{}: {}", arg.name, format_json(&arg.value)))
+            .collect::<Vec<_>>();
+        write!(f, "{}.{}({})", self.pallet, self.call, args.join(", "))?;
+        if let Some(ref raw) = self.raw {
+            write!(f, " undecoded: {}", raw)?;
+        }
+        Ok(())
+    }
+}
+
+/// The signer and the signed extra of an extrinsic.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtrinsicSignature {
+    pub signer: String,
+    pub nonce: Index,
+    pub tip: NumStr<Balance>,
+    pub era: String,
+}
+
+/// An extrinsic decoded with the metadata.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodedExtrinsic {
+    pub hash: Hash,
+    /// `None` for the unsigned extrinsics, e.g. the timestamp.
+    pub signature: Option<ExtrinsicSignature>,
+    pub call: DecodedCall,
+}
+
+fn format_json(value: &JsonValue) -> String {
+    match value {
+        JsonValue::String(value) => value.clone(),
+        value => value.to_string(),
+    }
+}
+
+fn format_address(address: &Address) -> String {
+    match address {
+        Address::Id(who) => who.to_string(),
+        Address::Index(index) => format!("index {}", index),
+    }
+}
+
+fn format_era(era: Era) -> String {
+    match era {
+        Era::Immortal => "immortal".into(),
+        Era::Mortal(period, phase) => format!("mortal, period {} phase {}", period, phase),
+    }
+}
+
+/// Removes the whitespaces, `T::`, `<T as Trait>::` and the `<T>`, `<T, I>` suffixes.
+fn normalize_type(ty: &str) -> String {
+    let mut ty = ty
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>();
+    loop {
+        if ty.starts_with("T::") {
+            ty = ty[3..].into();
+        } else if let Some(pos) = ty.find(">::").filter(|_| ty.starts_with('<')) {
+            ty = ty[pos + 3..].into();
+        } else {
+            break;
+        }
+    }
+    for suffix in &["<T>", "<T,I>"] {
+        if ty.ends_with(suffix) {
+            ty.truncate(ty.len() - suffix.len());
+        }
+    }
+    ty
+}
+
+/// Returns `Inner` of `Wrapper<Inner>`.
+fn generic_arg<'a>(ty: &'a str, wrapper: &str) -> Option<&'a str> {
+    if ty.len() > wrapper.len() + 2
+        && ty.starts_with(wrapper)
+        && ty[wrapper.len()..].starts_with('<')
+        && ty.ends_with('>')
+    {
+        Some(&ty[wrapper.len() + 1..ty.len() - 1])
+    } else {
+        None
+    }
+}
+
+/// Splits the types of a tuple at the top level commas.
+fn split_tuple(types: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let (mut depth, mut start) = (0, 0);
+    for (i, c) in types.char_indices() {
+        match c {
+            '<' | '(' | '[' => depth += 1,
+            '>' | ')' | ']' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(&types[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if start < types.len() {
+        parts.push(&types[start..]);
+    }
+    parts
+}
+
+fn take<'a>(input: &mut &'a [u8], len: usize) -> Result<&'a [u8]> {
+    if input.len() < len {
+        return Err(anyhow!("Not enough data to decode {} bytes", len));
+    }
+    let (bytes, rest) = input.split_at(len);
+    *input = rest;
+    Ok(bytes)
+}
+
+fn decode_bytes<'a>(input: &mut &'a [u8]) -> Result<&'a [u8]> {
+    let len = <Compact<u32>>::decode(input)?.0;
+    take(input, len as usize)
+}
+
+/// Skips the topics of an event record.
+fn decode_topics(input: &mut &[u8]) -> Result<()> {
+    let len = <Compact<u32>>::decode(input)?.0 as usize;
+    let len = len
+        .checked_mul(std::mem::size_of::<Hash>())
+        .ok_or_else(|| anyhow!("Too many topics: {}", len))?;
+    take(input, len).map(|_| ())
+}
+
+/// Decodes a fieldless enum by the names of its variants.
+fn decode_variant(ty: &str, variants: &[&str], input: &mut &[u8]) -> Result<JsonValue> {
+    let index = u8::decode(input)?;
+    variants
+        .get(index as usize)
+        .map(|variant| json!(variant))
+        .ok_or_else(|| anyhow!("Invalid variant {} of `{}`", index, ty))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codec::Encode;
+
+    #[test]
+    fn test_decode_value() {
+        let decoder = Decoder {
+            modules: BTreeMap::new(),
+        };
+        let who = AccountId::from([1u8; 32]);
+        let data = (vec![who.clone()], 100u128, Some(7u32)).encode();
+        let input = &mut &data[..];
+        assert_eq!(
+            decoder.decode_value("Vec<T::AccountId>", input).unwrap(),
+            Some(json!([who.to_string()]))
+        );
+        assert_eq!(
+            decoder.decode_value("BalanceOf<T>", input).unwrap(),
+            Some(json!("100"))
+        );
+        assert_eq!(
+            decoder
+                .decode_value("Option<<T as Trait>::BlockNumber>", input)
+                .unwrap(),
+            Some(json!(7))
+        );
+        assert!(input.is_empty());
+
+        let data = (1u32, 2u32).encode();
+        let input = &mut &data[..];
+        assert_eq!(
+            decoder.decode_value("(u32, Weird<T>)", input).unwrap(),
+            None
+        );
+        assert_eq!(input.len(), 8);
+        assert_eq!(
+            decoder.decode_value("(AssetId, u32)", input).unwrap(),
+            Some(json!([1, 2]))
+        );
+
+        let data = (
+            Phase::Finalization,
+            1u8,
+            <Result<(), DispatchError>>::Ok(()),
+        )
+            .encode();
+        let input = &mut &data[..];
+        assert_eq!(
+            decoder.decode_value("Phase", input).unwrap(),
+            Some(json!("Finalization"))
+        );
+        assert_eq!(
+            decoder.decode_value("Chain", input).unwrap(),
+            Some(json!("Bitcoin"))
+        );
+        assert_eq!(
+            decoder.decode_value("DispatchResult", input).unwrap(),
+            Some(json!("Ok"))
+        );
+        assert!(input.is_empty());
+        assert!(decoder.decode_value("Side", &mut &[2u8][..]).is_err());
+    }
+
+    #[test]
+    fn test_decode_events() {
+        let mut modules = BTreeMap::new();
+        modules.insert(
+            2,
+            ModuleMeta {
+                name: "XAssets".into(),
+                events: vec![
+                    EventMeta {
+                        name: "Moved".into(),
+                        arguments: vec!["AssetId".into(), "T::AccountId".into()],
+                    },
+                    EventMeta {
+                        name: "Weird".into(),
+                        arguments: vec!["u32".into(), "Weird".into()],
+                    },
+                ],
+                ..Default::default()
+            },
+        );
+        let decoder = Decoder { modules };
+
+        let who = AccountId::from([1u8; 32]);
+        let mut data = Compact(3u32).encode();
+        data.extend((Phase::ApplyExtrinsic(1), 2u8, 0u8, 1u32, who.clone()).encode());
+        data.extend(Vec::<Hash>::new().encode());
+        data.extend((Phase::Finalization, 2u8, 1u8, 5u32, 9u8).encode());
+
+        let events = decoder.decode_events(&data).unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].0, Phase::ApplyExtrinsic(1));
+        assert_eq!(
+            events[0].1.to_string(),
+            format!("XAssets.Moved(1, {})", who)
+        );
+        assert_eq!(events[1].1.args, vec![json!(5)]);
+        assert_eq!(events[1].1.raw.as_deref(), Some("0x09"));
+    }
+
+    #[test]
+    fn test_decode_events_after_unknown() {
+        let mut modules = BTreeMap::new();
+        modules.insert(
+            0,
+            ModuleMeta {
+                name: "System".into(),
+                events: vec![EventMeta {
+                    name: "ExtrinsicSuccess".into(),
+                    arguments: vec!["DispatchInfo".into()],
+                }],
+                ..Default::default()
+            },
+        );
+        let decoder = Decoder { modules };
+
+        let info = DispatchInfo {
+            weight: 10,
+            ..Default::default()
+        };
+        let mut data = Compact(2u32).encode();
+        data.extend((Phase::ApplyExtrinsic(1), 9u8, 0u8, [0xffu8; 3]).encode());
+        data.extend(Vec::<Hash>::new().encode());
+        data.extend((Phase::ApplyExtrinsic(1), 0u8, 0u8, info).encode());
+        data.extend(Vec::<Hash>::new().encode());
+
+        let events = decoder.decode_events(&data).unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].1.pallet, "Unknown(9)");
+        assert_eq!(events[0].1.raw.as_deref(), Some("0xffffff00"));
+        assert_eq!(events[1].0, Phase::ApplyExtrinsic(1));
+        assert_eq!(
+            (events[1].1.pallet.as_str(), events[1].1.event.as_str()),
+            ("System", "ExtrinsicSuccess")
+        );
+        assert_eq!(events[1].1.args[0]["weight"], json!(10));
+        assert!(events[1].1.raw.is_none());
+    }
+
+    #[test]
+    fn test_decode_extrinsic() {
+        let mut modules = BTreeMap::new();
+        modules.insert(
+            8,
+            ModuleMeta {
+                name: "XAssets".into(),
+                calls: vec![CallMeta {
+                    name: "transfer".into(),
+                    arguments: vec![
+                        ("dest".into(), "<T::Lookup as StaticLookup>::Source".into()),
+                        ("id".into(), "Compact<AssetId>".into()),
+                        ("value".into(), "Compact<BalanceOf<T>>".into()),
+                    ],
+                }],
+                errors: vec!["InsufficientBalance".into()],
+                ..Default::default()
+            },
+        );
+        let decoder = Decoder { modules };
+
+        let dest = AccountId::from([2u8; 32]);
+        let call = (
+            8u8,
+            0u8,
+            Address::Id(dest.clone()),
+            Compact(1u32),
+            Compact(100u128),
+        );
+        let signature = MultiSignature::Sr25519(Default::default());
+        let unsigned = (EXTRINSIC_VERSION, call.clone()).encode().encode();
+        let signed = (
+            EXTRINSIC_VERSION | 0b1000_0000,
+            Address::Id(AccountId::from([1u8; 32])),
+            signature,
+            Era::Immortal,
+            Compact(3u32),
+            Compact(5u128),
+            call,
+        )
+            .encode()
+            .encode();
+
+        let extrinsic = decoder.decode_extrinsic(&unsigned).unwrap();
+        assert!(extrinsic.signature.is_none());
+        assert_eq!(
+            extrinsic.call.to_string(),
+            format!("XAssets.transfer(dest: {}, id: 1, value: 100)", dest)
+        );
+
+        let extrinsic = decoder.decode_extrinsic(&signed).unwrap();
+        let signature = extrinsic.signature.unwrap();
+        assert_eq!((signature.nonce, signature.tip), (3, NumStr(5)));
+        assert_eq!(signature.era, "immortal");
+        assert!(extrinsic.call.raw.is_none());
+
+        let error = DispatchError::Module {
+            index: 8,
+            error: 0,
+            message: None,
+        };
+        assert_eq!(decoder.dispatch_error(error), "XAssets.InsufficientBalance");
+    }
+}
@@ -0,0 +1,542 @@
+//! Signs and submits the extrinsics according to the global transaction options.
+
+use std::{str::FromStr, sync::Mutex, time::Duration};
+
+use anyhow::{anyhow, Result};
+use codec::{Decode, Encode};
+use serde::{Deserialize, Serialize, Serializer};
+use sp_runtime::{
+    generic::{Era, SignedPayload},
+    ApplyExtrinsicResult,
+};
+use subxt::{
+    balances::TransferCall, events::Raw, sudo::SudoEventsDecoder, system::Phase, Call, Encoded,
+    Event, EventsDecoder, RawEvent, SignedExtra, Signer,
+};
+
+use crate::{
+    amount::format_pcx,
+    connection::ConnectionOptions,
+    output::{NumStr, OutputType},
+    rpc::{Rpc, RuntimeDispatchInfo},
+    runtime::{
+        primitives::{AccountId, Balance, BlockNumber, Hash, Index},
+        xpallets::{
+            xassets::XAssetsEventsDecoder, xassets_registrar::XAssetsRegistrarEventsDecoder,
+            xmining_asset::XMiningAssetEventsDecoder, xstaking::XStakingEventsDecoder,
+        },
+        ChainXClient, ChainXExtra, ChainXRuntime, ChainXSigner,
+    },
+    serde_hex, serde_num_str,
+};
+
+/// When the submission of an extrinsic is considered done.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum WaitFor {
+    /// Return the extrinsic hash once it's accepted by the transaction pool.
+    None,
+    /// Return once the extrinsic is included in a block.
+    InBlock,
+    /// Return once the block including the extrinsic is finalized.
+    Finalized,
+}
+
+impl WaitFor {
+    pub const VARIANTS: [&'static str; 3] = ["none", "in-block", "finalized"];
+}
+
+impl Default for WaitFor {
+    fn default() -> Self {
+        Self::Finalized
+    }
+}
+
+impl FromStr for WaitFor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Self::None),
+            "in-block" => Ok(Self::InBlock),
+            "finalized" => Ok(Self::Finalized),
+            _ => Err(format!(
+                "Invalid value `{}`, expected one of {:?}",
+                s,
+                Self::VARIANTS
+            )),
+        }
+    }
+}
+
+/// Options shared by all the extrinsic-submitting commands.
+#[derive(Clone, Debug, Default)]
+pub struct TxOptions {
+    /// Dry run the extrinsic and estimate the fee instead of submitting it.
+    pub dry_run: bool,
+    /// When the submission is considered done.
+    pub wait: WaitFor,
+    /// Give up waiting for the extrinsic after the timeout.
+    pub timeout: Option<Duration>,
+    /// Nonce of the first extrinsic, the next index of the signer by default.
+    pub nonce: Option<Index>,
+    /// Tip for the block author.
+    pub tip: Balance,
+    /// The number of blocks the extrinsic is valid for, immortal by default.
+    pub mortal_period: Option<u64>,
+    /// Output type of the submission result.
+    pub output: OutputType,
+}
+
+impl TxOptions {
+    /// Builds the unsigned transaction of the SCALE encoded `call` with the given nonce.
+    pub async fn build(
+        &self,
+        rpc: &Rpc,
+        signer: AccountId,
+        call: Vec<u8>,
+        nonce: Index,
+    ) -> Result<UnsignedTransaction> {
+        let version = rpc.runtime_version(None).await?;
+        let mortality = match self.mortal_period {
+            Some(period) => {
+                let block_hash = rpc.finalized_head().await?;
+                let header = rpc
+                    .header(Some(block_hash))
+                    .await?
+                    .ok_or_else(|| anyhow!("Header of {:?} not found", block_hash))?;
+                Some(Mortality {
+                    period,
+                    block_number: header.number,
+                    block_hash,
+                })
+            }
+            None => None,
+        };
+        Ok(UnsignedTransaction {
+            signer,
+            call,
+            nonce,
+            tip: self.tip,
+            mortality,
+            genesis_hash: rpc.genesis_hash().await?,
+            spec_version: version.spec_version,
+            transaction_version: version.transaction_version,
+        })
+    }
+}
+
+/// The block the mortal transaction is valid from.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Mortality {
+    /// The number of blocks the transaction is valid for.
+    pub period: u64,
+    /// The block number the mortal era begins.
+    pub block_number: BlockNumber,
+    /// The block hash the mortal era begins.
+    pub block_hash: Hash,
+}
+
+/// Everything required to sign a transaction offline.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnsignedTransaction {
+    pub signer: AccountId,
+    #[serde(with = "serde_hex")]
+    pub call: Vec<u8>,
+    pub nonce: Index,
+    #[serde(with = "serde_num_str")]
+    pub tip: Balance,
+    /// `None` for the immortal transaction.
+    pub mortality: Option<Mortality>,
+    pub genesis_hash: Hash,
+    pub spec_version: u32,
+    pub transaction_version: u32,
+}
+
+impl UnsignedTransaction {
+    fn extra(&self) -> ChainXExtra<ChainXRuntime> {
+        let extra = ChainXExtra::new(
+            self.spec_version,
+            self.transaction_version,
+            self.nonce,
+            self.genesis_hash,
+        )
+        .with_tip(self.tip);
+        if let Some(ref mortality) = self.mortality {
+            let era = Era::mortal(mortality.period, mortality.block_number.into());
+            extra.with_era(era, mortality.block_hash)
+        } else {
+            extra
+        }
+    }
+
+    /// Signs the transaction, returns the SCALE encoded signed extrinsic.
+    pub async fn sign(&self, signer: &ChainXSigner) -> Result<Vec<u8>> {
+        if signer.account_id() != &self.signer {
+            return Err(anyhow!(
+                "The transaction should be signed by {}, but got {}",
+                self.signer,
+                signer.account_id()
+            ));
+        }
+        let payload = SignedPayload::new(Encoded(self.call.clone()), self.extra().extra())
+            .map_err(|err| anyhow!("Failed to build the signed payload: {:?}", err))?;
+        let extrinsic = signer
+            .sign(payload)
+            .await
+            .map_err(|err| anyhow!("Failed to sign the transaction: {}", err))?;
+        Ok(extrinsic.encode())
+    }
+}
+
+/// Dispatch result and fee of a dry run extrinsic.
+#[derive(Clone, Debug)]
+pub struct DryRunResult {
+    pub result: ApplyExtrinsicResult,
+    pub info: RuntimeDispatchInfo,
+}
+
+impl DryRunResult {
+    /// Dry runs the SCALE encoded signed extrinsic at the latest block.
+    pub async fn fetch(rpc: &Rpc, extrinsic: Vec<u8>) -> Result<Self> {
+        let result = rpc.dry_run(extrinsic.clone(), None).await?;
+        let info = rpc.query_info(extrinsic, None).await?;
+        Ok(Self { result, info })
+    }
+
+    pub fn print(&self, output: OutputType) -> Result<()> {
+        output.print(self, || {
+            println!("Dry run result: {:?}", self.result);
+            println!("Weight: {}", self.info.weight);
+            println!("Class: {:?}", self.info.class);
+            println!("Partial fee: {}", format_pcx(self.info.partial_fee));
+        })
+    }
+}
+
+impl Serialize for DryRunResult {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serde_json::json!({
+            "result": format!("{:?}", self.result),
+            "weight": self.info.weight,
+            "class": self.info.class,
+            "partialFee": NumStr(self.info.partial_fee),
+        })
+        .serialize(serializer)
+    }
+}
+
+/// Dry runs the SCALE encoded signed extrinsic and prints the dispatch result and fee.
+pub async fn dry_run(rpc: &Rpc, extrinsic: Vec<u8>, output: OutputType) -> Result<()> {
+    DryRunResult::fetch(rpc, extrinsic).await?.print(output)
+}
+
+/// Result of a submitted extrinsic.
+#[derive(Clone, Debug)]
+pub struct TxOutcome {
+    /// Hash of the extrinsic.
+    pub extrinsic: Hash,
+    /// Hash of the block including the extrinsic, `None` if not waiting for the inclusion.
+    pub block: Option<Hash>,
+    /// Index of the extrinsic in the block.
+    pub index: Option<u32>,
+    /// Events emitted by the extrinsic.
+    pub events: Vec<RawEvent>,
+}
+
+impl TxOutcome {
+    /// Returns the first event `E` emitted by the extrinsic.
+    pub fn find_event<E: Event<ChainXRuntime>>(&self) -> Result<Option<E>> {
+        for event in &self.events {
+            if event.module == E::MODULE && event.variant == E::EVENT {
+                return Ok(Some(E::decode(&mut &event.data[..])?));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns true if the extrinsic has been included in a block.
+    pub fn is_included(&self) -> bool {
+        self.block.is_some()
+    }
+
+    pub fn print(&self, output: OutputType) -> Result<()> {
+        output.print(self, || {
+            println!("Extrinsic: {:?}", self.extrinsic);
+            match self.block {
+                Some(block) => println!("Block:     {:?}", block),
+                None => println!("Block:     pending"),
+            }
+            if let Some(index) = self.index {
+                println!("Index:     {}", index);
+            }
+            if self.is_included() {
+                println!("Events:");
+                for event in &self.events {
+                    println!(
+                        "  {}::{} 0x{}",
+                        event.module,
+                        event.variant,
+                        hex::encode(&event.data)
+                    );
+                }
+            }
+        })
+    }
+}
+
+impl Serialize for TxOutcome {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct EventView<'a> {
+            module: &'a str,
+            variant: &'a str,
+            #[serde(with = "serde_hex")]
+            data: &'a [u8],
+        }
+
+        #[derive(Serialize)]
+        struct TxOutcomeView<'a> {
+            extrinsic: Hash,
+            block: Option<Hash>,
+            index: Option<u32>,
+            events: Vec<EventView<'a>>,
+        }
+
+        TxOutcomeView {
+            extrinsic: self.extrinsic,
+            block: self.block,
+            index: self.index,
+            events: self
+                .events
+                .iter()
+                .map(|event| EventView {
+                    module: &event.module,
+                    variant: &event.variant,
+                    data: &event.data,
+                })
+                .collect(),
+        }
+        .serialize(serializer)
+    }
+}
+
+/// A call handled by the `Submitter`.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Submission {
+    /// The call was dry run instead of being submitted.
+    DryRun(DryRunResult),
+    Submitted(TxOutcome),
+}
+
+impl Submission {
+    /// Returns the outcome of the submitted extrinsic, `None` for a dry run.
+    pub fn outcome(&self) -> Option<&TxOutcome> {
+        match self {
+            Self::DryRun(_) => None,
+            Self::Submitted(outcome) => Some(outcome),
+        }
+    }
+
+    pub fn print(&self, output: OutputType) -> Result<()> {
+        match self {
+            Self::DryRun(result) => result.print(output),
+            Self::Submitted(outcome) => outcome.print(output),
+        }
+    }
+}
+
+/// Signs the calls with the signer and submits them.
+pub struct Submitter {
+    client: ChainXClient,
+    /// Shared by all the submissions of the command.
+    rpc: Rpc,
+    signer: ChainXSigner,
+    options: TxOptions,
+    /// Nonce of the next extrinsic, tracked locally after the first submission.
+    ///
+    /// Only advanced once an extrinsic is submitted, the dry runs reuse the nonce.
+    next_nonce: Mutex<Option<Index>>,
+}
+
+impl Submitter {
+    pub async fn new(
+        client: ChainXClient,
+        connection: &ConnectionOptions,
+        signer: ChainXSigner,
+        options: TxOptions,
+    ) -> Result<Self> {
+        Ok(Self {
+            client,
+            rpc: Rpc::new(connection).await?,
+            signer,
+            options,
+            next_nonce: Mutex::new(None),
+        })
+    }
+
+    /// Returns the connection to the node the calls are submitted to.
+    pub fn rpc(&self) -> &Rpc {
+        &self.rpc
+    }
+
+    /// Returns the nonce of the next extrinsic.
+    async fn next_nonce(&self) -> Result<Index> {
+        let tracked = *self.next_nonce.lock().expect("Lock is poisoned");
+        match tracked.or(self.options.nonce) {
+            Some(nonce) => Ok(nonce),
+            None => self.rpc.account_next_index(self.signer.account_id()).await,
+        }
+    }
+
+    /// Submits the call, waits for it according to `--wait` and prints the outcome.
+    ///
+    /// Returns `None` in the dry run mode, in which the call is never submitted.
+    pub async fn submit<C: Call<ChainXRuntime> + Send + Sync>(
+        &self,
+        call: C,
+    ) -> Result<Option<TxOutcome>> {
+        let submission = self.submit_quietly(call).await?;
+        submission.print(self.options.output)?;
+        match submission {
+            Submission::DryRun(_) => Ok(None),
+            Submission::Submitted(outcome) => Ok(Some(outcome)),
+        }
+    }
+
+    /// Submits the call like `submit` without printing the result, for the commands that
+    /// report several calls in a single document.
+    pub async fn submit_quietly<C: Call<ChainXRuntime> + Send + Sync>(
+        &self,
+        call: C,
+    ) -> Result<Submission> {
+        let decoder = self.client.events_decoder::<C>();
+        let call = self.client.encode(call)?;
+        let rpc = &self.rpc;
+        let nonce = self.next_nonce().await?;
+        let extrinsic = self
+            .options
+            .build(rpc, self.signer.account_id().clone(), call.0, nonce)
+            .await?
+            .sign(&self.signer)
+            .await?;
+
+        if self.options.dry_run {
+            let result = DryRunResult::fetch(rpc, extrinsic).await?;
+            return Ok(Submission::DryRun(result));
+        }
+
+        let outcome = match submit_and_wait(rpc, extrinsic, &self.options, &decoder).await {
+            Ok(outcome) => {
+                *self.next_nonce.lock().expect("Lock is poisoned") = Some(nonce + 1);
+                outcome
+            }
+            Err(err) => {
+                // The extrinsic may have been rejected or accepted, let the node tell the next
+                // nonce instead of leaving a gap.
+                let next_nonce = rpc.account_next_index(self.signer.account_id()).await.ok();
+                *self.next_nonce.lock().expect("Lock is poisoned") = next_nonce;
+                return Err(err);
+            }
+        };
+        Ok(Submission::Submitted(outcome))
+    }
+}
+
+/// Returns the events decoder of all the modules known here, for the extrinsics of any call.
+pub fn events_decoder(client: &ChainXClient) -> EventsDecoder<ChainXRuntime> {
+    let mut decoder = client.events_decoder::<TransferCall<ChainXRuntime>>();
+    decoder.with_sudo();
+    decoder.with_x_assets();
+    decoder.with_x_assets_registrar();
+    decoder.with_x_mining_asset();
+    decoder.with_x_staking();
+    decoder
+}
+
+/// Submits the SCALE encoded signed extrinsic, waits for it according to `--wait` and
+/// `--timeout`.
+pub async fn submit_and_wait(
+    rpc: &Rpc,
+    extrinsic: Vec<u8>,
+    options: &TxOptions,
+    decoder: &EventsDecoder<ChainXRuntime>,
+) -> Result<TxOutcome> {
+    let watch = watch(rpc, extrinsic, options.wait, decoder);
+    match options.timeout {
+        Some(timeout) => async_std::future::timeout(timeout, watch)
+            .await
+            .map_err(|_| anyhow!("Timed out after {:?} waiting for the extrinsic", timeout))?,
+        None => watch.await,
+    }
+}
+
+/// Submits the SCALE encoded signed extrinsic and waits for it.
+async fn watch(
+    rpc: &Rpc,
+    extrinsic: Vec<u8>,
+    wait: WaitFor,
+    decoder: &EventsDecoder<ChainXRuntime>,
+) -> Result<TxOutcome> {
+    let block = match wait {
+        WaitFor::None => {
+            let extrinsic = rpc.submit_extrinsic(extrinsic).await?;
+            return Ok(TxOutcome {
+                extrinsic,
+                block: None,
+                index: None,
+                events: Vec::new(),
+            });
+        }
+        WaitFor::InBlock | WaitFor::Finalized => {
+            rpc.submit_and_watch_extrinsic(extrinsic.clone(), wait == WaitFor::Finalized)
+                .await?
+        }
+    };
+
+    let extrinsic_hash = sp_core::blake2_256(&extrinsic).into();
+    let chain_block = rpc
+        .block(Some(block))
+        .await?
+        .ok_or_else(|| anyhow!("Block {:?} not found", block))?;
+    let index = chain_block
+        .block
+        .extrinsics
+        .iter()
+        .position(|xt| xt.encode() == extrinsic)
+        .ok_or_else(|| {
+            anyhow!(
+                "Extrinsic {:?} not found in block {:?}",
+                extrinsic_hash,
+                block
+            )
+        })? as u32;
+
+    let mut events = Vec::new();
+    let data = rpc.events(Some(block)).await?;
+    for (phase, raw) in decoder.decode_events(&mut &data[..])? {
+        if phase != Phase::ApplyExtrinsic(index) {
+            continue;
+        }
+        match raw {
+            Raw::Event(event) => events.push(event),
+            Raw::Error(err) => {
+                return Err(anyhow!(
+                    "Extrinsic {:?} failed in block {:?}: {:?}",
+                    extrinsic_hash,
+                    block,
+                    err
+                ))
+            }
+        }
+    }
+
+    Ok(TxOutcome {
+        extrinsic: extrinsic_hash,
+        block: Some(block),
+        index: Some(index),
+        events,
+    })
+}
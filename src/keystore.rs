@@ -0,0 +1,298 @@
+//! Encrypted on-disk keystore of the named accounts.
+//!
+//! Each account is stored in a standalone `<name>.json` file, the secret URI
+//! is encrypted by AES-256-GCM with a key derived from the user password via scrypt.
+
+use std::{
+    fs::{self, DirBuilder, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use aes_gcm::{
+    aead::{generic_array::GenericArray, Aead, NewAead},
+    Aes256Gcm,
+};
+use anyhow::{anyhow, Result};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use subxt::Signer;
+
+use crate::{
+    runtime::{primitives::AccountId, ChainXSigner, CryptoScheme},
+    serde_hex,
+};
+
+const KEY_FILE_EXTENSION: &str = "json";
+const SALT_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+const KEY_LEN: usize = 32;
+
+/// scrypt parameters, `N = 2^15, r = 8, p = 1`.
+const SCRYPT_LOG_N: u8 = 15;
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
+
+/// Environment variable used as the keystore password, prompts for it if not set.
+pub const KEYSTORE_PASSWORD_ENV: &str = "KEYSTORE_PASSWORD";
+
+/// The encrypted account stored in the keystore.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyFile {
+    /// Name of the account.
+    pub name: String,
+    /// Cryptography scheme of the account.
+    #[serde(default, with = "serde_scheme")]
+    pub scheme: CryptoScheme,
+    /// Account id of the account.
+    pub account_id: AccountId,
+    /// Salt of the scrypt key derivation.
+    #[serde(with = "serde_hex")]
+    pub salt: Vec<u8>,
+    /// Nonce of the AES-256-GCM encryption.
+    #[serde(with = "serde_hex")]
+    pub nonce: Vec<u8>,
+    /// The encrypted secret URI.
+    #[serde(with = "serde_hex")]
+    pub ciphertext: Vec<u8>,
+}
+
+impl KeyFile {
+    /// Encrypts the secret URI with the given password.
+    pub fn encrypt(name: &str, scheme: CryptoScheme, suri: &str, password: &str) -> Result<Self> {
+        let account_id = signer_for(scheme, suri)?.account_id().clone();
+
+        let mut salt = vec![0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        let mut nonce = vec![0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce);
+
+        let cipher = cipher_for(password, &salt)?;
+        let ciphertext = cipher
+            .encrypt(GenericArray::from_slice(&nonce), suri.as_bytes())
+            .map_err(|err| anyhow!("Failed to encrypt the secret: {:?}", err))?;
+
+        Ok(Self {
+            name: name.into(),
+            scheme,
+            account_id,
+            salt,
+            nonce,
+            ciphertext,
+        })
+    }
+
+    /// Decrypts the secret URI with the given password.
+    pub fn decrypt(&self, password: &str) -> Result<String> {
+        let cipher = cipher_for(password, &self.salt)?;
+        let plaintext = cipher
+            .decrypt(
+                GenericArray::from_slice(&self.nonce),
+                self.ciphertext.as_slice(),
+            )
+            .map_err(|_| anyhow!("Failed to decrypt `{}`, wrong password?", self.name))?;
+        Ok(String::from_utf8(plaintext)?)
+    }
+}
+
+fn cipher_for(password: &str, salt: &[u8]) -> Result<Aes256Gcm> {
+    let params = scrypt::ScryptParams::new(SCRYPT_LOG_N, SCRYPT_R, SCRYPT_P)
+        .map_err(|err| anyhow!("Invalid scrypt params: {:?}", err))?;
+    let mut key = [0u8; KEY_LEN];
+    scrypt::scrypt(password.as_bytes(), salt, &params, &mut key)
+        .map_err(|err| anyhow!("Failed to derive the key from password: {:?}", err))?;
+    Ok(Aes256Gcm::new(GenericArray::from_slice(&key)))
+}
+
+fn signer_for(scheme: CryptoScheme, suri: &str) -> Result<ChainXSigner> {
+    ChainXSigner::from_uri(scheme, suri, None)
+        .map_err(|err| anyhow!("Failed to generate {} Pair from uri: {:?}", scheme, err))
+}
+
+/// Lowercase string serialization/deserialization of `CryptoScheme`.
+mod serde_scheme {
+    use serde::{de, ser, Deserialize};
+
+    use crate::runtime::CryptoScheme;
+
+    pub fn serialize<S: ser::Serializer>(
+        scheme: &CryptoScheme,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&scheme.to_string().to_lowercase())
+    }
+
+    pub fn deserialize<'de, D: de::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<CryptoScheme, D::Error> {
+        let scheme = String::deserialize(deserializer)?;
+        scheme.parse::<CryptoScheme>().map_err(de::Error::custom)
+    }
+}
+
+/// Reads the keystore password from the environment variable or the terminal.
+pub fn read_password(prompt: &str) -> Result<String> {
+    if let Ok(password) = std::env::var(KEYSTORE_PASSWORD_ENV) {
+        Ok(password)
+    } else {
+        Ok(rpassword::read_password_from_tty(Some(prompt))?)
+    }
+}
+
+/// Local keystore, one file per account.
+#[derive(Clone, Debug)]
+pub struct Keystore {
+    path: PathBuf,
+}
+
+impl Keystore {
+    /// Opens the keystore at the given directory, creates it if not exists.
+    pub fn open<P: Into<PathBuf>>(path: P) -> Result<Self> {
+        let path = path.into();
+        let mut builder = DirBuilder::new();
+        builder.recursive(true);
+        #[cfg(unix)]
+        std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+        builder.create(&path)?;
+        Ok(Self { path })
+    }
+
+    /// Returns the default keystore directory, i.e., `<data_dir>/chainx-cli/keystore`.
+    pub fn default_path() -> Result<PathBuf> {
+        let mut path = dirs::data_dir().ok_or_else(|| anyhow!("Can not find the data dir"))?;
+        path.push("chainx-cli");
+        path.push("keystore");
+        Ok(path)
+    }
+
+    /// Returns the directory of the keystore.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn key_file_path(&self, name: &str) -> Result<PathBuf> {
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(anyhow!(
+                "Invalid account name `{}`, only [a-zA-Z0-9_-] are allowed",
+                name
+            ));
+        }
+        let mut path = self.path.join(name);
+        path.set_extension(KEY_FILE_EXTENSION);
+        Ok(path)
+    }
+
+    /// Encrypts and stores the secret URI as the account `name`.
+    pub fn add(
+        &self,
+        name: &str,
+        scheme: CryptoScheme,
+        suri: &str,
+        password: &str,
+    ) -> Result<KeyFile> {
+        let path = self.key_file_path(name)?;
+        if path.exists() {
+            return Err(anyhow!("Account `{}` already exists", name));
+        }
+        let key_file = KeyFile::encrypt(name, scheme, suri, password)?;
+        // Only readable by the owner.
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options.open(path)?;
+        file.write_all(serde_json::to_string_pretty(&key_file)?.as_bytes())?;
+        Ok(key_file)
+    }
+
+    /// Returns the stored account `name`.
+    pub fn get(&self, name: &str) -> Result<KeyFile> {
+        let path = self.key_file_path(name)?;
+        if !path.exists() {
+            return Err(anyhow!("Account `{}` not found in the keystore", name));
+        }
+        Ok(serde_json::from_slice(&fs::read(path)?)?)
+    }
+
+    /// Returns all the stored accounts, sorted by name.
+    pub fn list(&self) -> Result<Vec<KeyFile>> {
+        let mut key_files = Vec::new();
+        for entry in fs::read_dir(&self.path)? {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) == Some(KEY_FILE_EXTENSION) {
+                key_files.push(serde_json::from_slice::<KeyFile>(&fs::read(path)?)?);
+            }
+        }
+        key_files.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(key_files)
+    }
+
+    /// Removes the account `name` from the keystore.
+    pub fn remove(&self, name: &str) -> Result<()> {
+        let key_file = self.get(name)?;
+        fs::remove_file(self.key_file_path(&key_file.name)?)?;
+        Ok(())
+    }
+
+    /// Decrypts the secret URI of the account `name`.
+    pub fn export(&self, name: &str, password: &str) -> Result<String> {
+        self.get(name)?.decrypt(password)
+    }
+
+    /// Returns the signer of the account `name`.
+    pub fn signer(&self, name: &str, password: &str) -> Result<ChainXSigner> {
+        let key_file = self.get(name)?;
+        signer_for(key_file.scheme, &key_file.decrypt(password)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_file_encryption() {
+        let key_file =
+            KeyFile::encrypt("alice", CryptoScheme::Sr25519, "//Alice", "password").unwrap();
+        assert_eq!(
+            key_file.account_id,
+            sp_keyring::AccountKeyring::Alice.to_account_id()
+        );
+        assert_eq!(key_file.decrypt("password").unwrap(), "//Alice");
+        assert!(key_file.decrypt("wrong password").is_err());
+    }
+
+    #[test]
+    fn test_keystore() {
+        let path = std::env::temp_dir().join(format!("chainx-cli-keystore-{}", OsRng.next_u64()));
+        let keystore = Keystore::open(&path).unwrap();
+
+        let scheme = CryptoScheme::Ed25519;
+        keystore.add("bob", scheme, "//Bob", "password").unwrap();
+        assert!(keystore.add("bob", scheme, "//Bob", "password").is_err());
+        assert!(keystore.add("../bob", scheme, "//Bob", "password").is_err());
+        assert_eq!(keystore.list().unwrap().len(), 1);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = |path: &Path| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+            assert_eq!(mode(&path), 0o700);
+            assert_eq!(mode(&keystore.key_file_path("bob").unwrap()), 0o600);
+        }
+        assert_eq!(keystore.export("bob", "password").unwrap(), "//Bob");
+        assert_eq!(
+            keystore.signer("bob", "password").unwrap().account_id(),
+            &sp_keyring::Ed25519Keyring::Bob.to_account_id()
+        );
+
+        keystore.remove("bob").unwrap();
+        assert!(keystore.list().unwrap().is_empty());
+
+        fs::remove_dir_all(path).unwrap();
+    }
+}
@@ -1,7 +1,17 @@
+pub mod amount;
 mod app;
+pub mod backend;
+pub mod cache;
+pub mod connection;
+pub mod decoder;
+pub mod extrinsic;
+pub mod keystore;
+pub mod output;
 pub mod rpc;
 pub mod runtime;
 mod serde;
+pub mod state;
+pub mod storage;
 mod utils;
 
 pub use self::app::App;
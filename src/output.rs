@@ -0,0 +1,106 @@
+//! Text or JSON output of the commands.
+
+use std::{collections::BTreeMap, fmt::Display};
+
+use anyhow::Result;
+use serde::Serialize;
+use structopt::clap::arg_enum;
+use subxt::{balances::BalanceLock, system::AccountInfo};
+
+use crate::{
+    runtime::{
+        primitives::{Balance, Index},
+        ChainXRuntime,
+    },
+    serde_num_str,
+};
+
+arg_enum! {
+  #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+  pub enum OutputType {
+      Text,
+      Json,
+  }
+}
+
+impl Default for OutputType {
+    fn default() -> Self {
+        Self::Text
+    }
+}
+
+impl OutputType {
+    /// Prints `value` as a single JSON document in the JSON mode, otherwise calls `text`.
+    pub fn print<T: Serialize>(self, value: &T, text: impl FnOnce()) -> Result<()> {
+        match self {
+            Self::Text => text(),
+            Self::Json => println!("{}", serde_json::to_string_pretty(value)?),
+        }
+        Ok(())
+    }
+
+    /// Prints `value` as one line of JSON in the JSON mode, otherwise calls `text`.
+    ///
+    /// Used by the streaming commands, which print a document per item.
+    pub fn print_line<T: Serialize>(self, value: &T, text: impl FnOnce()) -> Result<()> {
+        match self {
+            Self::Text => text(),
+            Self::Json => println!("{}", serde_json::to_string(value)?),
+        }
+        Ok(())
+    }
+}
+
+/// A number serialized as a string, to keep the precision of `u128` in JSON.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+pub struct NumStr<T: Display>(#[serde(with = "serde_num_str")] pub T);
+
+/// Converts the balances of the map to `NumStr`.
+pub fn num_str_map<K: Clone + Ord, V: Copy + Display>(
+    map: &BTreeMap<K, V>,
+) -> BTreeMap<K, NumStr<V>> {
+    map.iter().map(|(k, v)| (k.clone(), NumStr(*v))).collect()
+}
+
+/// JSON view of `AccountInfo`.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountInfoView {
+    pub nonce: Index,
+    pub refcount: u32,
+    pub free: NumStr<Balance>,
+    pub reserved: NumStr<Balance>,
+    pub misc_frozen: NumStr<Balance>,
+    pub fee_frozen: NumStr<Balance>,
+}
+
+impl From<&AccountInfo<ChainXRuntime>> for AccountInfoView {
+    fn from(info: &AccountInfo<ChainXRuntime>) -> Self {
+        Self {
+            nonce: info.nonce,
+            refcount: info.refcount,
+            free: NumStr(info.data.free),
+            reserved: NumStr(info.data.reserved),
+            misc_frozen: NumStr(info.data.misc_frozen),
+            fee_frozen: NumStr(info.data.fee_frozen),
+        }
+    }
+}
+
+/// JSON view of `BalanceLock`.
+#[derive(Clone, Debug, Serialize)]
+pub struct BalanceLockView {
+    pub id: String,
+    pub amount: NumStr<Balance>,
+    pub reasons: String,
+}
+
+impl From<&BalanceLock<Balance>> for BalanceLockView {
+    fn from(lock: &BalanceLock<Balance>) -> Self {
+        Self {
+            id: String::from_utf8_lossy(&lock.id).into(),
+            amount: NumStr(lock.amount),
+            reasons: format!("{:?}", lock.reasons),
+        }
+    }
+}
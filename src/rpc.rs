@@ -1,182 +1,807 @@
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, str::FromStr, sync::Arc};
 
 use anyhow::{anyhow, Result};
-use codec::Decode;
-use jsonrpsee::{
-    common::{to_value as to_json_value, Params},
-    Client,
+use codec::{Decode, Encode};
+use futures::{
+    stream::{self, BoxStream},
+    StreamExt, TryStreamExt,
 };
+use jsonrpsee::common::{to_value as to_json_value, Params};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sp_consensus_babe::{digests::PreDigest, BABE_ENGINE_ID};
 use sp_core::{
-    storage::{StorageData, StorageKey},
-    twox_128,
+    storage::{StorageChangeSet, StorageData, StorageKey},
+    Bytes,
 };
-use subxt::system::AccountInfo;
-
-use crate::runtime::{
-    primitives::{AccountId, AssetId, Balance, BlockNumber, Hash},
-    xpallets::{
-        xassets::AssetType,
-        xmining_asset::{AssetLedger, MinerLedger, MiningWeight},
-        xstaking::{NominatorLedger, Unbonded, ValidatorLedger, ValidatorProfile, VoteWeight},
+use sp_runtime::{
+    generic::{Block, SignedBlock},
+    traits::Header as _,
+    ApplyExtrinsicResult,
+};
+use subxt::system::{AccountInfo, System};
+
+use crate::{
+    backend::{Backend, NodeBackend},
+    cache::StorageCache,
+    connection::{ConnectionOptions, TransportError},
+    runtime::{
+        primitives::{AccountId, AssetId, Balance, BlockNumber, Hash, Index},
+        xpallets::{
+            xassets::AssetType,
+            xmining_asset::{AssetLedger, MinerLedger, MiningWeight},
+            xstaking::{
+                LockedType, NominatorLedger, Unbonded, ValidatorLedger, ValidatorProfile,
+                VoteWeight,
+            },
+        },
+        ChainXRuntime,
+    },
+    state::StateFile,
+    storage::{
+        storage_prefix_for, Blake2_128Concat, StorageDoubleMap, StorageHasher, StorageMap,
+        Twox64Concat,
     },
-    ChainXRuntime,
 };
 
-const TWOX_HASH_LEN: usize = 16; // 8 bytes hex
-const BLAKE_HASH_LEN: usize = 32; // 16 bytes hex
-const STORAGE_PREFIX_LEN: usize = 64; // 32 bytes hex
+/// Number of the keys fetched in one `state_getKeysPaged` request.
+const PAGE_SIZE: u32 = 1000;
+/// Number of the keys queried in one `state_queryStorageAt` request.
+const BATCH_SIZE: usize = 500;
+/// Number of the `state_queryStorageAt` requests in flight at most.
+const MAX_CONCURRENT_REQUESTS: usize = 8;
+
+pub const ACCOUNT: StorageMap<Blake2_128Concat, AccountId, AccountInfo<ChainXRuntime>> =
+    StorageMap::new("System", "Account");
+pub const ASSET_BALANCE: StorageDoubleMap<
+    Blake2_128Concat,
+    AccountId,
+    Twox64Concat,
+    AssetId,
+    BTreeMap<AssetType, Balance>,
+> = StorageDoubleMap::new("XAssets", "AssetBalance");
+pub const TOTAL_ASSET_BALANCE: StorageMap<Twox64Concat, AssetId, BTreeMap<AssetType, Balance>> =
+    StorageMap::new("XAssets", "TotalAssetBalance");
+pub const ASSET_LEDGERS: StorageMap<Twox64Concat, AssetId, AssetLedger<MiningWeight, BlockNumber>> =
+    StorageMap::new("XMiningAsset", "AssetLedgers");
+pub const MINER_LEDGERS: StorageDoubleMap<
+    Twox64Concat,
+    AccountId,
+    Twox64Concat,
+    AssetId,
+    MinerLedger<MiningWeight, BlockNumber>,
+> = StorageDoubleMap::new("XMiningAsset", "MinerLedgers");
+pub const VALIDATORS: StorageMap<Twox64Concat, AccountId, ValidatorProfile<BlockNumber>> =
+    StorageMap::new("XStaking", "Validators");
+pub const NOMINATIONS: StorageDoubleMap<
+    Twox64Concat,
+    AccountId,
+    Twox64Concat,
+    AccountId,
+    NominatorLedger<Balance, VoteWeight, BlockNumber>,
+> = StorageDoubleMap::new("XStaking", "Nominations");
+pub const LOCKS: StorageMap<Blake2_128Concat, AccountId, BTreeMap<LockedType, Balance>> =
+    StorageMap::new("XStaking", "Locks");
+pub const VALIDATOR_LEDGERS: StorageMap<
+    Twox64Concat,
+    AccountId,
+    ValidatorLedger<Balance, VoteWeight, BlockNumber>,
+> = StorageMap::new("XStaking", "ValidatorLedgers");
+
+/// Runtime version, only the fields required by the transaction signing.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimeVersion {
+    pub spec_version: u32,
+    pub transaction_version: u32,
+}
+
+/// Dispatch class of the extrinsic.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DispatchClass {
+    Normal,
+    Operational,
+    Mandatory,
+}
+
+/// Information related to a dispatchable's class, weight, and fee.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimeDispatchInfo {
+    pub weight: u64,
+    pub class: DispatchClass,
+    /// The partial fee is returned as either a number or a string by the node.
+    #[serde(deserialize_with = "deserialize_number_or_string")]
+    pub partial_fee: Balance,
+}
+
+fn deserialize_number_or_string<'de, D>(deserializer: D) -> Result<Balance, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum NumberOrString {
+        Number(u64),
+        String(String),
+    }
+
+    match NumberOrString::deserialize(deserializer)? {
+        NumberOrString::Number(n) => Ok(n.into()),
+        NumberOrString::String(s) => s.parse::<Balance>().map_err(serde::de::Error::custom),
+    }
+}
 
-fn storage_prefix_for(module: &str, storage_name: &str) -> Vec<u8> {
-    let mut storage_prefix = twox_128(module.as_bytes()).to_vec();
-    storage_prefix.extend_from_slice(&twox_128(storage_name.as_bytes()));
-    storage_prefix
+/// Status of the extrinsic watched by `author_submitAndWatchExtrinsic`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TransactionStatus {
+    Future,
+    Ready,
+    Broadcast(Vec<String>),
+    InBlock(Hash),
+    Retracted(Hash),
+    FinalityTimeout(Hash),
+    Finalized(Hash),
+    Usurped(Hash),
+    Dropped,
+    Invalid,
 }
 
+/// The block with the opaque extrinsics.
+pub type ChainBlock =
+    SignedBlock<Block<<ChainXRuntime as System>::Header, <ChainXRuntime as System>::Extrinsic>>;
+
+/// A block given by the number or the `0x`-prefixed hash.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BlockId {
+    Number(BlockNumber),
+    Hash(Hash),
+}
+
+impl FromStr for BlockId {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.starts_with("0x") {
+            s[2..]
+                .parse::<Hash>()
+                .map(Self::Hash)
+                .map_err(|err| format!("Invalid block hash `{}`: {:?}", s, err))
+        } else {
+            s.parse::<BlockNumber>()
+                .map(Self::Number)
+                .map_err(|err| format!("Invalid block number `{}`: {}", s, err))
+        }
+    }
+}
+
+/// RPC client that reconnects and retries the read-only requests on failure.
+///
+/// The requests are served by a live node, or by the state file if `--state` is given.
 #[derive(Clone)]
 pub struct Rpc {
-    client: Client,
+    backend: Arc<dyn Backend>,
+    options: ConnectionOptions,
+    cache: Option<StorageCache>,
 }
 
 impl Rpc {
-    pub async fn new<U: AsRef<str>>(url: U) -> Result<Self> {
-        let client = jsonrpsee::ws_client(url.as_ref()).await?;
-        Ok(Self { client })
+    pub async fn new(options: &ConnectionOptions) -> Result<Self> {
+        let backend: Arc<dyn Backend> = match options.state {
+            Some(ref path) => Arc::new(StateFile::load(path)?),
+            None => Arc::new(NodeBackend::connect(options).await?),
+        };
+        Self::with_backend(backend, options)
+    }
+
+    /// Returns the client of `backend`, `options.url` and `options.state` are ignored.
+    pub fn with_backend(backend: Arc<dyn Backend>, options: &ConnectionOptions) -> Result<Self> {
+        Ok(Self {
+            backend,
+            options: options.clone(),
+            cache: options.storage_cache()?,
+        })
+    }
+
+    /// Re-establishes the connection to the node, e.g. to renew a lost subscription.
+    pub async fn reconnect(&self) -> Result<()> {
+        self.backend.reconnect().await
+    }
+
+    /// Sends the request once, fails if there is no response within the request timeout.
+    async fn request_once<T: DeserializeOwned>(&self, method: &str, params: Params) -> Result<T> {
+        let timeout = self.options.request_timeout();
+        let value = async_std::future::timeout(timeout, self.backend.request(method, params))
+            .await
+            .map_err(|_| {
+                TransportError(format!(
+                    "Request `{}` timed out after {:?}",
+                    method, timeout
+                ))
+            })??;
+        serde_json::from_value(value)
+            .map_err(|err| anyhow!("Invalid response of `{}`: {}", method, err))
+    }
+
+    /// Sends the idempotent request, reconnects and retries it with backoff if the connection
+    /// fails.
+    ///
+    /// The error responses and the undecodable responses are returned at once, retrying never
+    /// changes them.
+    async fn request<T: DeserializeOwned>(&self, method: &str, params: Params) -> Result<T> {
+        if !self.backend.retryable() {
+            return self.request_once(method, params).await;
+        }
+        let mut attempt = 0;
+        loop {
+            match self.request_once(method, params.clone()).await {
+                Ok(value) => return Ok(value),
+                Err(err) if err.is::<TransportError>() && attempt < self.options.max_retries => {
+                    let backoff = self.options.backoff(attempt);
+                    log::warn!(
+                        "Request `{}` failed: {}, retrying in {:?}",
+                        method,
+                        err,
+                        backoff
+                    );
+                    async_std::task::sleep(backoff).await;
+                    attempt += 1;
+                    if let Err(err) = self.backend.reconnect().await {
+                        log::warn!("{:#}", err);
+                    }
+                }
+                Err(err) => return Err(err),
+            }
+        }
     }
 
     pub async fn genesis_hash(&self) -> Result<Hash> {
         let params = Params::Array(vec![to_json_value(0)?]);
-        let hash = self.client.request("chain_getBlockHash", params).await?;
+        let hash = self.request("chain_getBlockHash", params).await?;
         Ok(hash)
     }
 
-    #[allow(unused)]
-    pub async fn get_keys(&self, key: StorageKey, hash: Option<Hash>) -> Result<Vec<StorageKey>> {
-        let params = Params::Array(vec![to_json_value(key)?, to_json_value(hash)?]);
-        let data = self.client.request("state_getKeys", params).await?;
-        Ok(data)
+    /// Returns the hash of block `number`, the latest block if `number` is `None`.
+    pub async fn block_hash(&self, number: Option<BlockNumber>) -> Result<Option<Hash>> {
+        let params = Params::Array(vec![to_json_value(number)?]);
+        let hash = self.request("chain_getBlockHash", params).await?;
+        Ok(hash)
     }
 
-    #[allow(unused)]
-    pub async fn get_accounts(&self, hash: Option<Hash>) -> Result<Vec<String>> {
-        let prefix = storage_prefix_for("System", "Account");
-        let data = self.get_keys(StorageKey(prefix), hash).await?;
+    pub async fn finalized_head(&self) -> Result<Hash> {
+        let hash = self.request("chain_getFinalizedHead", Params::None).await?;
+        Ok(hash)
+    }
+
+    /// Returns the header of block `hash`, the latest block if `hash` is `None`.
+    pub async fn header(
+        &self,
+        hash: Option<Hash>,
+    ) -> Result<Option<<ChainXRuntime as System>::Header>> {
+        let params = Params::Array(vec![to_json_value(hash)?]);
+        let header = self.request("chain_getHeader", params).await?;
+        Ok(header)
+    }
+
+    /// Returns the hash of `block`, the latest block if `block` is `None`.
+    pub async fn block_id_hash(&self, block: Option<BlockId>) -> Result<Hash> {
+        match block {
+            Some(BlockId::Hash(hash)) => Ok(hash),
+            Some(BlockId::Number(number)) => self
+                .block_hash(Some(number))
+                .await?
+                .ok_or_else(|| anyhow!("Block #{} not found", number)),
+            None => self
+                .block_hash(None)
+                .await?
+                .ok_or_else(|| anyhow!("Failed to fetch the latest block")),
+        }
+    }
+
+    pub async fn latest_block_number(&self) -> Result<BlockNumber> {
+        let header = self
+            .header(None)
+            .await?
+            .ok_or_else(|| anyhow!("Failed to fetch the latest block"))?;
+        Ok(header.number)
+    }
 
-        // System Account (32 bytes hex) + hash (16 bytes hex) = 48 bytes hex
-        let accounts = data
+    /// Subscribes to the new heads, or to the finalized heads if `finalized`.
+    ///
+    /// The finalized heads are consecutive, the blocks finalized at once are yielded one by one
+    /// in order. The new heads may skip or repeat the numbers on the forks.
+    ///
+    /// Unlike the requests, the subscription is neither timed out nor renewed if the connection
+    /// is lost, the stream just stops yielding. Callers time out on the heads and subscribe
+    /// again after `reconnect`, with the last block seen as `after` to fill in the finalized
+    /// heads missed in between.
+    pub async fn subscribe_heads(
+        &self,
+        finalized: bool,
+        after: Option<BlockNumber>,
+    ) -> Result<BoxStream<'static, Result<<ChainXRuntime as System>::Header>>> {
+        let (method, unsubscribe) = if finalized {
+            (
+                "chain_subscribeFinalizedHeads",
+                "chain_unsubscribeFinalizedHeads",
+            )
+        } else {
+            ("chain_subscribeNewHeads", "chain_unsubscribeNewHeads")
+        };
+        let subscription = self
+            .backend
+            .subscription_client()
+            .await?
+            .subscribe(method, Params::None, unsubscribe)
+            .await?;
+
+        let rpc = self.clone();
+        let heads = stream::try_unfold(
+            (subscription, after),
+            move |(mut subscription, last): (_, Option<BlockNumber>)| {
+                let rpc = rpc.clone();
+                async move {
+                    let header: <ChainXRuntime as System>::Header = subscription.next().await;
+                    let headers = match last {
+                        Some(last) if finalized => rpc.headers_after(last, header).await?,
+                        _ => vec![header],
+                    };
+                    let last = headers.last().map(|header| header.number);
+                    let headers = stream::iter(headers.into_iter().map(Ok::<_, anyhow::Error>));
+                    Ok(Some((headers, (subscription, last))))
+                }
+            },
+        )
+        .try_flatten();
+        Ok(heads.boxed())
+    }
+
+    /// Returns the headers after block `number` up to `header`, in order.
+    async fn headers_after(
+        &self,
+        number: BlockNumber,
+        header: <ChainXRuntime as System>::Header,
+    ) -> Result<Vec<<ChainXRuntime as System>::Header>> {
+        let mut headers = vec![header];
+        while let Some(oldest) = headers.last().filter(|oldest| oldest.number > number + 1) {
+            let parent_hash = oldest.parent_hash;
+            let parent = self
+                .header(Some(parent_hash))
+                .await?
+                .ok_or_else(|| anyhow!("Header of block {:?} not found", parent_hash))?;
+            headers.push(parent);
+        }
+        headers.reverse();
+        Ok(headers)
+    }
+
+    /// Returns the author of the block, the session validator of the BABE pre-digest.
+    pub async fn block_author(
+        &self,
+        header: &<ChainXRuntime as System>::Header,
+    ) -> Result<Option<AccountId>> {
+        let authority_index = header
+            .digest
+            .logs()
+            .iter()
+            .find_map(|log| log.pre_runtime_try_to::<PreDigest>(&BABE_ENGINE_ID))
+            .map(|pre_digest| pre_digest.authority_index());
+        match authority_index {
+            Some(index) => {
+                // The authority index refers to the validators before the block, which differ
+                // from the ones after the block at a session boundary.
+                let validators = self
+                    .get_session_validators(Some(header.parent_hash))
+                    .await?;
+                Ok(validators.get(index as usize).cloned())
+            }
+            None => Ok(None),
+        }
+    }
+
+    pub async fn runtime_version(&self, hash: Option<Hash>) -> Result<RuntimeVersion> {
+        let params = Params::Array(vec![to_json_value(hash)?]);
+        let version = self.request("state_getRuntimeVersion", params).await?;
+        Ok(version)
+    }
+
+    /// Returns the next nonce of `who`, including the transactions in the pool.
+    pub async fn account_next_index(&self, who: &AccountId) -> Result<Index> {
+        let params = Params::Array(vec![to_json_value(who)?]);
+        let index = self.request("system_accountNextIndex", params).await?;
+        Ok(index)
+    }
+
+    /// Submits the SCALE encoded signed extrinsic, returns the extrinsic hash.
+    pub async fn submit_extrinsic(&self, extrinsic: Vec<u8>) -> Result<Hash> {
+        let params = Params::Array(vec![to_json_value(Bytes(extrinsic))?]);
+        let hash = self.request_once("author_submitExtrinsic", params).await?;
+        Ok(hash)
+    }
+
+    /// Submits the SCALE encoded signed extrinsic and watches its status, returns the hash of
+    /// the block that includes it, or the finalized one if `finalized` is true.
+    ///
+    /// Never retried nor timed out, the extrinsic may have been submitted. The `Submitter`
+    /// bounds the watch with `--timeout`.
+    pub async fn submit_and_watch_extrinsic(
+        &self,
+        extrinsic: Vec<u8>,
+        finalized: bool,
+    ) -> Result<Hash> {
+        let params = Params::Array(vec![to_json_value(Bytes(extrinsic))?]);
+        let mut subscription = self
+            .backend
+            .subscription_client()
+            .await?
+            .subscribe(
+                "author_submitAndWatchExtrinsic",
+                params,
+                "author_unwatchExtrinsic",
+            )
+            .await?;
+        loop {
+            let status: TransactionStatus = subscription.next().await;
+            match status {
+                TransactionStatus::Future
+                | TransactionStatus::Ready
+                | TransactionStatus::Broadcast(_)
+                | TransactionStatus::Retracted(_) => continue,
+                TransactionStatus::InBlock(block_hash) if !finalized => return Ok(block_hash),
+                TransactionStatus::InBlock(_) => continue,
+                TransactionStatus::Finalized(block_hash) => return Ok(block_hash),
+                status => return Err(anyhow!("Extrinsic not included: {:?}", status)),
+            }
+        }
+    }
+
+    /// Returns the SCALE encoded runtime metadata at block `hash`.
+    pub async fn metadata(&self, hash: Option<Hash>) -> Result<Vec<u8>> {
+        let params = Params::Array(vec![to_json_value(hash)?]);
+        let metadata: Bytes = self.request("state_getMetadata", params).await?;
+        Ok(metadata.0)
+    }
+
+    /// Returns the block `hash`, the latest block if `hash` is `None`.
+    pub async fn block(&self, hash: Option<Hash>) -> Result<Option<ChainBlock>> {
+        let params = Params::Array(vec![to_json_value(hash)?]);
+        let block = self.request("chain_getBlock", params).await?;
+        Ok(block)
+    }
+
+    /// Returns the SCALE encoded `System::Events` at block `hash`.
+    pub async fn events(&self, hash: Option<Hash>) -> Result<Vec<u8>> {
+        let key = StorageKey(storage_prefix_for("System", "Events"));
+        let data = self.get_storage(key, hash).await?;
+        Ok(data.map(|data| data.0).unwrap_or_default())
+    }
+
+    /// Dry runs the SCALE encoded signed extrinsic, returns the `ApplyExtrinsicResult`.
+    pub async fn dry_run(
+        &self,
+        extrinsic: Vec<u8>,
+        hash: Option<Hash>,
+    ) -> Result<ApplyExtrinsicResult> {
+        let params = Params::Array(vec![to_json_value(Bytes(extrinsic))?, to_json_value(hash)?]);
+        let result: Bytes = self.request("system_dryRun", params).await?;
+        Ok(Decode::decode(&mut result.0.as_slice())?)
+    }
+
+    /// Returns the weight, class and the partial fee of the SCALE encoded extrinsic.
+    pub async fn query_info(
+        &self,
+        extrinsic: Vec<u8>,
+        hash: Option<Hash>,
+    ) -> Result<RuntimeDispatchInfo> {
+        let params = Params::Array(vec![to_json_value(Bytes(extrinsic))?, to_json_value(hash)?]);
+        let info = self.request("payment_queryInfo", params).await?;
+        Ok(info)
+    }
+
+    /// Returns at most `count` keys under `prefix` after `start_key`, in the lexicographic order.
+    pub async fn get_keys_paged(
+        &self,
+        prefix: StorageKey,
+        count: u32,
+        start_key: Option<StorageKey>,
+        hash: Option<Hash>,
+    ) -> Result<Vec<StorageKey>> {
+        let params = Params::Array(vec![
+            to_json_value(prefix)?,
+            to_json_value(count)?,
+            to_json_value(start_key)?,
+            to_json_value(hash)?,
+        ]);
+        let keys = self.request("state_getKeysPaged", params).await?;
+        Ok(keys)
+    }
+
+    /// Returns the values of `keys` at block `hash`, the missing ones are skipped.
+    pub async fn query_storage_at(
+        &self,
+        keys: Vec<StorageKey>,
+        hash: Option<Hash>,
+    ) -> Result<Vec<(StorageKey, StorageData)>> {
+        let params = Params::Array(vec![to_json_value(keys)?, to_json_value(hash)?]);
+        let change_sets: Vec<StorageChangeSet<Hash>> =
+            self.request("state_queryStorageAt", params).await?;
+        Ok(change_sets
             .into_iter()
-            .map(|x| hex::encode(x.0))
-            .map(|x| format!("0x{}", &x[STORAGE_PREFIX_LEN + BLAKE_HASH_LEN..]))
-            .collect::<Vec<_>>();
-        Ok(accounts)
+            .flat_map(|change_set| change_set.changes)
+            .filter_map(|(key, data)| data.map(|data| (key, data)))
+            .collect())
     }
 
-    pub async fn get_pairs(
+    pub async fn get_storage(
         &self,
         key: StorageKey,
         hash: Option<Hash>,
-    ) -> Result<Vec<(StorageKey, StorageData)>> {
+    ) -> Result<Option<StorageData>> {
         let params = Params::Array(vec![to_json_value(key)?, to_json_value(hash)?]);
-        let data = self.client.request("state_getPairs", params).await?;
+        let data = self.request("state_getStorage", params).await?;
         Ok(data)
     }
 
-    pub async fn get_accounts_info(
+    /// Returns the stream of the key pages under `prefix`, along with the block hash they are
+    /// fetched at, all the pages are fetched at the same block.
+    fn key_pages(
         &self,
+        prefix: StorageKey,
         hash: Option<Hash>,
-    ) -> Result<BTreeMap<AccountId, AccountInfo<ChainXRuntime>>> {
-        let prefix = storage_prefix_for("System", "Account");
-        let data = self.get_pairs(StorageKey(prefix), hash).await?;
+    ) -> BoxStream<'_, Result<(Hash, Vec<StorageKey>)>> {
+        struct Page {
+            hash: Option<Hash>,
+            start_key: Option<StorageKey>,
+            done: bool,
+        }
+
+        let first = Page {
+            hash,
+            start_key: None,
+            done: false,
+        };
+        stream::try_unfold(first, move |page| {
+            let prefix = prefix.clone();
+            async move {
+                if page.done {
+                    return Ok(None);
+                }
+                let hash = self.pin_block(page.hash).await?;
+                let keys = self
+                    .get_keys_paged(prefix, PAGE_SIZE, page.start_key, Some(hash))
+                    .await?;
+                let next = Page {
+                    hash: Some(hash),
+                    start_key: keys.last().cloned(),
+                    done: keys.len() < PAGE_SIZE as usize,
+                };
+                Ok(Some(((hash, keys), next)))
+            }
+        })
+        .boxed()
+    }
+
+    /// Returns the stream of all the keys under `prefix`, fetched in pages.
+    pub fn iter_keys(
+        &self,
+        prefix: StorageKey,
+        hash: Option<Hash>,
+    ) -> BoxStream<'_, Result<StorageKey>> {
+        self.key_pages(prefix, hash)
+            .map_ok(|(_, keys)| stream::iter(keys.into_iter().map(Ok)))
+            .try_flatten()
+            .boxed()
+    }
+
+    /// Returns `hash`, or the hash of the latest block if `hash` is `None`.
+    async fn pin_block(&self, hash: Option<Hash>) -> Result<Hash> {
+        match hash {
+            Some(hash) => Ok(hash),
+            None => self
+                .block_hash(None)
+                .await?
+                .ok_or_else(|| anyhow!("Latest block hash not found")),
+        }
+    }
+
+    /// Returns the stream of all the raw `(key, value)` pairs under `prefix`, fetched in pages.
+    fn fetch_pairs(
+        &self,
+        prefix: StorageKey,
+        hash: Option<Hash>,
+    ) -> BoxStream<'_, Result<(StorageKey, StorageData)>> {
+        self.key_pages(prefix, hash)
+            .and_then(move |(hash, keys)| async move {
+                if keys.is_empty() {
+                    return Ok(Vec::new());
+                }
+                self.query_storage_at(keys, Some(hash)).await
+            })
+            .map_ok(|pairs| stream::iter(pairs.into_iter().map(Ok)))
+            .try_flatten()
+            .boxed()
+    }
+
+    /// Returns all the raw pairs under `prefix` from the cache, fetches and caches them on miss.
+    async fn cached_pairs(
+        &self,
+        cache: &StorageCache,
+        prefix: StorageKey,
+        hash: Option<Hash>,
+    ) -> Result<Vec<(StorageKey, StorageData)>> {
+        let hash = self.pin_block(hash).await?;
+        if let Some(pairs) = cache.get(hash, &prefix)? {
+            log::debug!("Storage cache hit: {:?} at {:?}", prefix, hash);
+            return Ok(pairs);
+        }
+        let pairs = self
+            .fetch_pairs(prefix.clone(), Some(hash))
+            .try_collect::<Vec<_>>()
+            .await?;
+        cache.put(hash, &prefix, &pairs)?;
+        Ok(pairs)
+    }
+
+    /// Returns the stream of all the raw `(key, value)` pairs under `prefix`.
+    ///
+    /// The pairs are read from the storage cache if enabled, otherwise fetched in pages.
+    pub fn iter_raw_storage(
+        &self,
+        prefix: StorageKey,
+        hash: Option<Hash>,
+    ) -> BoxStream<'_, Result<(StorageKey, StorageData)>> {
+        match self.cache {
+            Some(ref cache) => stream::once(self.cached_pairs(cache, prefix, hash))
+                .map_ok(|pairs| stream::iter(pairs.into_iter().map(Ok)))
+                .try_flatten()
+                .boxed(),
+            None => self.fetch_pairs(prefix, hash),
+        }
+    }
 
+    /// Returns the stream of all the `(key, value)` pairs under `prefix`.
+    pub fn iter_storage<V: Decode + Send + 'static>(
+        &self,
+        prefix: StorageKey,
+        hash: Option<Hash>,
+    ) -> BoxStream<'_, Result<(StorageKey, V)>> {
+        self.iter_raw_storage(prefix, hash)
+            .and_then(|(key, data)| async move {
+                let value = V::decode(&mut data.0.as_slice())?;
+                Ok((key, value))
+            })
+            .boxed()
+    }
+
+    /// Returns the values of `keys` at block `hash`, the missing ones are skipped.
+    ///
+    /// The keys are queried in batches, with a bounded number of the concurrent requests.
+    pub async fn query_storage_batched(
+        &self,
+        keys: Vec<StorageKey>,
+        hash: Option<Hash>,
+    ) -> Result<Vec<(StorageKey, StorageData)>> {
+        // Pin the block so that all the batches are queried at the same state.
+        let hash = self.pin_block(hash).await?;
+        let batches = stream::iter(keys.chunks(BATCH_SIZE))
+            .map(|batch| self.query_storage_at(batch.to_vec(), Some(hash)))
+            .buffer_unordered(MAX_CONCURRENT_REQUESTS)
+            .try_collect::<Vec<_>>()
+            .await?;
+        Ok(batches.into_iter().flatten().collect())
+    }
+
+    /// Returns the entries of `keys` in the storage map, the missing ones are skipped.
+    pub async fn get_map_entries<H, K, V>(
+        &self,
+        map: &StorageMap<H, K, V>,
+        keys: impl IntoIterator<Item = K>,
+        hash: Option<Hash>,
+    ) -> Result<BTreeMap<K, V>>
+    where
+        H: StorageHasher,
+        K: Encode + Decode + Ord,
+        V: Decode,
+    {
+        let keys = keys.into_iter().map(|key| map.key(&key)).collect();
         let mut result = BTreeMap::new();
-        for (key, value) in data {
-            let pubkey = &hex::encode(&key.0)[STORAGE_PREFIX_LEN + BLAKE_HASH_LEN..];
-            let account_id = pubkey
-                .parse::<AccountId>()
-                .map_err(|err| anyhow!("{}", err))?;
+        for (key, data) in self.query_storage_batched(keys, hash).await? {
+            let value = V::decode(&mut data.0.as_slice())?;
+            result.insert(map.decode_key(&key)?, value);
+        }
+        Ok(result)
+    }
 
-            let account_info: AccountInfo<ChainXRuntime> = Decode::decode(&mut value.0.as_slice())?;
+    /// Returns all the entries of the storage map.
+    pub async fn get_map<H, K, V>(
+        &self,
+        map: &StorageMap<H, K, V>,
+        hash: Option<Hash>,
+    ) -> Result<BTreeMap<K, V>>
+    where
+        H: StorageHasher,
+        K: Encode + Decode + Ord,
+        V: Decode + Send + 'static,
+    {
+        let mut pairs = self.iter_storage::<V>(map.prefix(), hash);
+        let mut result = BTreeMap::new();
+        while let Some((key, value)) = pairs.try_next().await? {
+            result.insert(map.decode_key(&key)?, value);
+        }
+        Ok(result)
+    }
 
-            result.insert(account_id, account_info);
+    /// Returns all the entries of the storage double map, grouped by the first key.
+    pub async fn get_double_map<H1, K1, H2, K2, V>(
+        &self,
+        map: &StorageDoubleMap<H1, K1, H2, K2, V>,
+        hash: Option<Hash>,
+    ) -> Result<BTreeMap<K1, BTreeMap<K2, V>>>
+    where
+        H1: StorageHasher,
+        K1: Encode + Decode + Ord,
+        H2: StorageHasher,
+        K2: Encode + Decode + Ord,
+        V: Decode + Send + 'static,
+    {
+        let mut pairs = self.iter_storage::<V>(map.prefix(), hash);
+        let mut result = BTreeMap::<K1, BTreeMap<K2, V>>::new();
+        while let Some((key, value)) = pairs.try_next().await? {
+            let (key1, key2) = map.decode_key(&key)?;
+            result.entry(key1).or_default().insert(key2, value);
         }
         Ok(result)
     }
 
+    #[allow(unused)]
+    pub async fn get_accounts(&self, hash: Option<Hash>) -> Result<Vec<String>> {
+        let mut keys = self.iter_keys(ACCOUNT.prefix(), hash);
+        let mut accounts = Vec::new();
+        while let Some(key) = keys.try_next().await? {
+            let account = ACCOUNT.decode_key(&key)?;
+            accounts.push(format!("0x{}", hex::encode(account)));
+        }
+        Ok(accounts)
+    }
+
+    pub async fn get_accounts_info(
+        &self,
+        hash: Option<Hash>,
+    ) -> Result<BTreeMap<AccountId, AccountInfo<ChainXRuntime>>> {
+        self.get_map(&ACCOUNT, hash).await
+    }
+
+    pub async fn get_account_info(
+        &self,
+        who: &AccountId,
+        hash: Option<Hash>,
+    ) -> Result<AccountInfo<ChainXRuntime>> {
+        match self.get_storage(ACCOUNT.key(who), hash).await? {
+            Some(data) => Ok(Decode::decode(&mut data.0.as_slice())?),
+            None => Ok(Default::default()),
+        }
+    }
+
     pub async fn get_asset_balance(
         &self,
         hash: Option<Hash>,
     ) -> Result<BTreeMap<AccountId, BTreeMap<AssetId, BTreeMap<AssetType, Balance>>>> {
-        let prefix = storage_prefix_for("XAssets", "AssetBalance");
-        let data = self.get_pairs(StorageKey(prefix), hash).await?;
-        let mut assets =
-            BTreeMap::<AccountId, BTreeMap<AssetId, BTreeMap<AssetType, Balance>>>::new();
-        for (key, value) in data {
-            let key = hex::encode(&key.0);
-            let hashed_key1_key1_hashed_key2_key2 = &key[STORAGE_PREFIX_LEN..];
-
-            let hashed_key1_key1 = &hashed_key1_key1_hashed_key2_key2[..BLAKE_HASH_LEN + 64];
-            let key1 = &hashed_key1_key1[BLAKE_HASH_LEN..];
-            let account = key1
-                .parse::<AccountId>()
-                .map_err(|err| anyhow!("{}", err))?;
-
-            let hashed_key2_key2 = &hashed_key1_key1_hashed_key2_key2[hashed_key1_key1.len()..];
-            let key2 = &hashed_key2_key2[TWOX_HASH_LEN..];
-            let mut asset_id = [0u8; 4];
-            asset_id.copy_from_slice(hex::decode(key2)?.as_slice());
-
-            let asset_balance: BTreeMap<AssetType, Balance> =
-                Decode::decode(&mut value.0.as_slice())?;
-
-            let entry = assets.entry(account).or_default();
-            entry.insert(AssetId::from_le_bytes(asset_id), asset_balance);
-        }
-        Ok(assets)
+        self.get_double_map(&ASSET_BALANCE, hash).await
     }
 
     pub async fn get_total_asset_balance(
         &self,
         hash: Option<Hash>,
     ) -> Result<BTreeMap<AssetId, BTreeMap<AssetType, Balance>>> {
-        let prefix = storage_prefix_for("XAssets", "TotalAssetBalance");
-        let data = self.get_pairs(StorageKey(prefix), hash).await?;
-        let mut total_asset_balance = BTreeMap::new();
-        for (key, value) in data {
-            let key = hex::encode(&key.0);
-            let hashed_key_key = &key[STORAGE_PREFIX_LEN..];
-            let key = &hashed_key_key[TWOX_HASH_LEN..];
-            let mut asset_id = [0u8; 4];
-            asset_id.copy_from_slice(hex::decode(key)?.as_slice());
-
-            let asset_balance: BTreeMap<AssetType, Balance> =
-                Decode::decode(&mut value.0.as_slice())?;
-
-            total_asset_balance.insert(AssetId::from_le_bytes(asset_id), asset_balance);
-        }
-        Ok(total_asset_balance)
+        self.get_map(&TOTAL_ASSET_BALANCE, hash).await
     }
 
     pub async fn get_asset_ledgers(
         &self,
         hash: Option<Hash>,
     ) -> Result<BTreeMap<AssetId, AssetLedger<MiningWeight, BlockNumber>>> {
-        let prefix = storage_prefix_for("XMiningAsset", "AssetLedgers");
-        let storage_key = StorageKey(prefix);
-        let data = self.get_pairs(storage_key, hash).await?;
-
-        let mut asset_ledgers = BTreeMap::new();
-        for (key, value) in data {
-            let key = hex::encode(&key.0);
-            let hashed_key_key = &key[STORAGE_PREFIX_LEN..];
-            let key = &hashed_key_key[TWOX_HASH_LEN..];
-            let mut asset_id = [0u8; 4];
-            asset_id.copy_from_slice(hex::decode(key)?.as_slice());
-
-            let asset_ledger: AssetLedger<MiningWeight, BlockNumber> =
-                Decode::decode(&mut value.0.as_slice())?;
-
-            asset_ledgers.insert(AssetId::from_le_bytes(asset_id), asset_ledger);
-        }
-        Ok(asset_ledgers)
+        self.get_map(&ASSET_LEDGERS, hash).await
     }
 
     pub async fn get_miner_ledgers(
@@ -184,64 +809,30 @@ impl Rpc {
         hash: Option<Hash>,
     ) -> Result<BTreeMap<AccountId, BTreeMap<AssetId, MinerLedger<MiningWeight, BlockNumber>>>>
     {
-        let prefix = storage_prefix_for("XMiningAsset", "MinerLedgers");
-        let data = self.get_pairs(StorageKey(prefix), hash).await?;
-
-        let mut miner_ledgers =
-            BTreeMap::<AccountId, BTreeMap<AssetId, MinerLedger<MiningWeight, BlockNumber>>>::new();
-        for (key, value) in data {
-            let key = hex::encode(&key.0);
-            let hashed_key1_key1_hashed_key2_key2 = &key[STORAGE_PREFIX_LEN..];
-
-            let hashed_key1_key1 = &hashed_key1_key1_hashed_key2_key2[..TWOX_HASH_LEN + 64];
-            let key1 = &hashed_key1_key1[TWOX_HASH_LEN..];
-            let account = key1
-                .parse::<AccountId>()
-                .map_err(|err| anyhow!("{}", err))?;
-
-            let hashed_key2_key2 = &hashed_key1_key1_hashed_key2_key2[hashed_key1_key1.len()..];
-            let key2 = &hashed_key2_key2[TWOX_HASH_LEN..];
-            let mut asset_id = [0u8; 4];
-            asset_id.copy_from_slice(hex::decode(key2)?.as_slice());
-
-            let miner_ledger: MinerLedger<MiningWeight, BlockNumber> =
-                Decode::decode(&mut value.0.as_slice())?;
-
-            let entry = miner_ledgers.entry(account).or_default();
-            entry.insert(AssetId::from_le_bytes(asset_id), miner_ledger);
-        }
-        Ok(miner_ledgers)
+        self.get_double_map(&MINER_LEDGERS, hash).await
     }
 
     pub async fn get_vesting_account(&self, hash: Option<Hash>) -> Result<AccountId> {
-        let prefix = storage_prefix_for("XStaking", "VestingAccount");
-        let data = self.get_pairs(StorageKey(prefix.clone()), hash).await?;
-        let mut vesting_account = Default::default();
-        for (_key, value) in data {
-            vesting_account = Decode::decode(&mut value.0.as_slice())?;
+        let key = storage_prefix_for("XStaking", "VestingAccount");
+        match self.get_storage(StorageKey(key), hash).await? {
+            Some(data) => Ok(Decode::decode(&mut data.0.as_slice())?),
+            None => Ok(Default::default()),
+        }
+    }
+
+    pub async fn get_session_validators(&self, hash: Option<Hash>) -> Result<Vec<AccountId>> {
+        let key = storage_prefix_for("Session", "Validators");
+        match self.get_storage(StorageKey(key), hash).await? {
+            Some(data) => Ok(Decode::decode(&mut data.0.as_slice())?),
+            None => Ok(Vec::new()),
         }
-        Ok(vesting_account)
     }
 
     pub async fn get_validators(
         &self,
         hash: Option<Hash>,
     ) -> Result<BTreeMap<AccountId, ValidatorProfile<BlockNumber>>> {
-        let prefix = storage_prefix_for("XStaking", "Validators");
-        let data = self.get_pairs(StorageKey(prefix), hash).await?;
-        let mut validator_profiles = BTreeMap::new();
-        for (key, value) in data {
-            let key = hex::encode(&key.0);
-            let hashed_key_key = &key[STORAGE_PREFIX_LEN..];
-            let key = &hashed_key_key[TWOX_HASH_LEN..];
-            let validator = key.parse::<AccountId>().map_err(|err| anyhow!("{}", err))?;
-
-            let validator_profile: ValidatorProfile<BlockNumber> =
-                Decode::decode(&mut value.0.as_slice())?;
-
-            validator_profiles.insert(validator, validator_profile);
-        }
-        Ok(validator_profiles)
+        self.get_map(&VALIDATORS, hash).await
     }
 
     pub async fn get_nominations(
@@ -250,56 +841,23 @@ impl Rpc {
     ) -> Result<
         BTreeMap<AccountId, BTreeMap<AccountId, NominatorLedger<Balance, VoteWeight, BlockNumber>>>,
     > {
-        let prefix = storage_prefix_for("XStaking", "Nominations");
-        let data = self.get_pairs(StorageKey(prefix), hash).await?;
-        let mut nominations = BTreeMap::<
-            AccountId,
-            BTreeMap<AccountId, NominatorLedger<Balance, VoteWeight, BlockNumber>>,
-        >::new();
-        for (key, value) in data {
-            let key = hex::encode(&key.0);
-            let hashed_key1_key1_hashed_key2_key2 = &key[STORAGE_PREFIX_LEN..];
-
-            let hashed_key1_key1 = &hashed_key1_key1_hashed_key2_key2[..TWOX_HASH_LEN + 64];
-            let key1 = &hashed_key1_key1[TWOX_HASH_LEN..];
-            let nominator = key1
-                .parse::<AccountId>()
-                .map_err(|err| anyhow!("{}", err))?;
-
-            let hashed_key2_key2 = &hashed_key1_key1_hashed_key2_key2[hashed_key1_key1.len()..];
-            let key2 = &hashed_key2_key2[TWOX_HASH_LEN..];
-            let nominee = key2
-                .parse::<AccountId>()
-                .map_err(|err| anyhow!("{}", err))?;
-
-            let nominator_ledger: NominatorLedger<Balance, VoteWeight, BlockNumber> =
-                Decode::decode(&mut value.0.as_slice())?;
-
-            let entry = nominations.entry(nominator).or_default();
-            entry.insert(nominee, nominator_ledger);
-        }
-        Ok(nominations)
+        self.get_double_map(&NOMINATIONS, hash).await
     }
 
     pub async fn get_validator_ledgers(
         &self,
         hash: Option<Hash>,
     ) -> Result<BTreeMap<AccountId, ValidatorLedger<Balance, VoteWeight, BlockNumber>>> {
-        let prefix = storage_prefix_for("XStaking", "ValidatorLedgers");
-        let data = self.get_pairs(StorageKey(prefix), hash).await?;
-        let mut validator_ledgers = BTreeMap::new();
-        for (key, value) in data {
-            let key = hex::encode(&key.0);
-            let hashed_key_key = &key[STORAGE_PREFIX_LEN..];
-            let key = &hashed_key_key[TWOX_HASH_LEN..];
-            let validator = key.parse::<AccountId>().map_err(|err| anyhow!("{}", err))?;
-
-            let validator_ledger: ValidatorLedger<Balance, VoteWeight, BlockNumber> =
-                Decode::decode(&mut value.0.as_slice())?;
-
-            validator_ledgers.insert(validator, validator_ledger);
-        }
-        Ok(validator_ledgers)
+        self.get_map(&VALIDATOR_LEDGERS, hash).await
+    }
+
+    /// Returns the staking locks of `accounts`, the accounts without any locks are skipped.
+    pub async fn get_locks(
+        &self,
+        accounts: impl IntoIterator<Item = AccountId>,
+        hash: Option<Hash>,
+    ) -> Result<BTreeMap<AccountId, BTreeMap<LockedType, Balance>>> {
+        self.get_map_entries(&LOCKS, accounts, hash).await
     }
 
     pub async fn get_staking_dividend(
@@ -309,7 +867,6 @@ impl Rpc {
     ) -> Result<BTreeMap<AccountId, Balance>> {
         let params = Params::Array(vec![to_json_value(who)?, to_json_value(hash)?]);
         let data: BTreeMap<AccountId, String> = self
-            .client
             .request("xstaking_getDividendByAccount", params)
             .await?;
         Ok(data
@@ -331,7 +888,6 @@ impl Rpc {
     ) -> Result<BTreeMap<AccountId, NominatorLedger<Balance, Balance, BlockNumber>>> {
         let params = Params::Array(vec![to_json_value(who)?, to_json_value(hash)?]);
         let data: BTreeMap<AccountId, NominatorLedger<String, String, BlockNumber>> = self
-            .client
             .request("xstaking_getNominationByAccount", params)
             .await?;
         Ok(data
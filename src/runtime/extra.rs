@@ -0,0 +1,90 @@
+use std::{fmt::Debug, marker::PhantomData};
+
+use codec::{Decode, Encode};
+use sp_runtime::{
+    generic::Era, traits::SignedExtension, transaction_validity::TransactionValidityError,
+};
+use subxt::{
+    balances::Balances,
+    extrinsic::{
+        ChargeTransactionPayment, CheckEra, CheckGenesis, CheckNonce, CheckSpecVersion,
+        CheckTxVersion, CheckWeight, SignedExtra,
+    },
+    system::System,
+};
+
+/// ChainX signed extra, the same as `DefaultExtra` except the era and tip are configurable.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, Debug)]
+pub struct ChainXExtra<T: System + Balances> {
+    spec_version: u32,
+    tx_version: u32,
+    nonce: T::Index,
+    genesis_hash: T::Hash,
+    era: Era,
+    /// Hash of the block where the mortal era begins, genesis hash for the immortal era.
+    checkpoint: T::Hash,
+    tip: <T as Balances>::Balance,
+}
+
+impl<T: System + Balances> ChainXExtra<T> {
+    /// Sets the mortal era starting from the block `checkpoint`.
+    pub fn with_era(mut self, era: Era, checkpoint: T::Hash) -> Self {
+        self.era = era;
+        self.checkpoint = checkpoint;
+        self
+    }
+
+    /// Sets the tip for the block author.
+    pub fn with_tip(mut self, tip: <T as Balances>::Balance) -> Self {
+        self.tip = tip;
+        self
+    }
+}
+
+impl<T: System + Balances + Clone + Debug + Eq + Send + Sync> SignedExtra<T> for ChainXExtra<T> {
+    type Extra = (
+        CheckSpecVersion<T>,
+        CheckTxVersion<T>,
+        CheckGenesis<T>,
+        CheckEra<T>,
+        CheckNonce<T>,
+        CheckWeight<T>,
+        ChargeTransactionPayment<T>,
+    );
+
+    fn new(spec_version: u32, tx_version: u32, nonce: T::Index, genesis_hash: T::Hash) -> Self {
+        ChainXExtra {
+            spec_version,
+            tx_version,
+            nonce,
+            genesis_hash,
+            era: Era::Immortal,
+            checkpoint: genesis_hash,
+            tip: Default::default(),
+        }
+    }
+
+    fn extra(&self) -> Self::Extra {
+        (
+            CheckSpecVersion(PhantomData, self.spec_version),
+            CheckTxVersion(PhantomData, self.tx_version),
+            CheckGenesis(PhantomData, self.genesis_hash),
+            CheckEra((self.era, PhantomData), self.checkpoint),
+            CheckNonce(self.nonce),
+            CheckWeight(PhantomData),
+            ChargeTransactionPayment(self.tip),
+        )
+    }
+}
+
+impl<T: System + Balances + Clone + Debug + Eq + Send + Sync> SignedExtension for ChainXExtra<T> {
+    const IDENTIFIER: &'static str = "ChainXExtra";
+    type AccountId = T::AccountId;
+    type Call = ();
+    type AdditionalSigned = <<Self as SignedExtra<T>>::Extra as SignedExtension>::AdditionalSigned;
+    type Pre = ();
+
+    fn additional_signed(&self) -> Result<Self::AdditionalSigned, TransactionValidityError> {
+        self.extra().additional_signed()
+    }
+}
@@ -1,4 +1,6 @@
+mod extra;
 pub mod primitives;
+mod signer;
 pub mod xpallets;
 
 use pallet_im_online::sr25519::AuthorityId as ImOnlineId;
@@ -9,16 +11,23 @@ use sp_finality_grandpa::AuthorityId as GrandpaId;
 use sp_runtime::{generic::Header, impl_opaque_keys, OpaqueExtrinsic};
 use subxt::{
     balances::{AccountData, Balances},
-    extrinsic::DefaultExtra,
     session::Session,
     sudo::Sudo,
     system::System,
-    Client, PairSigner, Runtime,
+    Client, Runtime,
+};
+
+pub use self::{
+    extra::ChainXExtra,
+    signer::{ChainXSigner, CryptoScheme},
 };
 
 use self::{
     primitives::*,
-    xpallets::{xassets::XAssets, xmining_asset::XMiningAsset, xstaking::XStaking},
+    xpallets::{
+        xassets::XAssets, xassets_registrar::XAssetsRegistrar, xmining_asset::XMiningAsset,
+        xstaking::XStaking,
+    },
 };
 
 /// Concrete type definitions for ChainX.
@@ -27,7 +36,7 @@ pub struct ChainXRuntime;
 
 impl Runtime for ChainXRuntime {
     type Signature = Signature;
-    type Extra = DefaultExtra<Self>;
+    type Extra = ChainXExtra<Self>;
 }
 
 impl System for ChainXRuntime {
@@ -96,6 +105,7 @@ impl Session for ChainXRuntime {
 }
 
 impl XAssets for ChainXRuntime {}
+impl XAssetsRegistrar for ChainXRuntime {}
 impl XMiningAsset for ChainXRuntime {}
 impl XStaking for ChainXRuntime {}
 
@@ -104,6 +114,3 @@ pub type ChainXClient = Client<ChainXRuntime>;
 
 /// ChainX `Pair` for ChainX runtime.
 pub type ChainXPair = sr25519::Pair;
-
-/// ChainX `PairSigner` for ChainX runtime.
-pub type ChainXSigner = PairSigner<ChainXRuntime, ChainXPair>;
@@ -0,0 +1,124 @@
+use std::{future::Future, pin::Pin};
+
+use sp_core::{crypto::SecretStringError, ecdsa, ed25519, sr25519, Pair};
+use structopt::clap::arg_enum;
+use subxt::{extrinsic::SignedPayload, PairSigner, Signer, UncheckedExtrinsic};
+
+use super::{
+    primitives::{AccountId, Index, Signature},
+    ChainXRuntime,
+};
+
+arg_enum! {
+    /// Cryptography scheme of the signer.
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    pub enum CryptoScheme {
+        Sr25519,
+        Ed25519,
+        Ecdsa,
+    }
+}
+
+impl Default for CryptoScheme {
+    fn default() -> Self {
+        Self::Sr25519
+    }
+}
+
+/// ChainX `PairSigner` for ChainX runtime, supports all the schemes of `MultiSignature`.
+pub enum ChainXSigner {
+    Sr25519(PairSigner<ChainXRuntime, sr25519::Pair>),
+    Ed25519(PairSigner<ChainXRuntime, ed25519::Pair>),
+    Ecdsa(PairSigner<ChainXRuntime, ecdsa::Pair>),
+}
+
+impl ChainXSigner {
+    /// Generates the signer of `scheme` from the secret URI.
+    ///
+    /// Supports the secret phrase, raw seed (`0x...`) and the derivation paths, e.g.,
+    /// `//Alice`, `<mnemonic>//stash///password`.
+    pub fn from_uri(
+        scheme: CryptoScheme,
+        uri: &str,
+        password: Option<&str>,
+    ) -> Result<Self, SecretStringError> {
+        Ok(match scheme {
+            CryptoScheme::Sr25519 => {
+                Self::Sr25519(PairSigner::new(sr25519::Pair::from_string(uri, password)?))
+            }
+            CryptoScheme::Ed25519 => {
+                Self::Ed25519(PairSigner::new(ed25519::Pair::from_string(uri, password)?))
+            }
+            CryptoScheme::Ecdsa => {
+                Self::Ecdsa(PairSigner::new(ecdsa::Pair::from_string(uri, password)?))
+            }
+        })
+    }
+
+    /// Signs an arbitrary message.
+    pub fn sign_message(&self, message: &[u8]) -> Signature {
+        match self {
+            Self::Sr25519(signer) => signer.signer().sign(message).into(),
+            Self::Ed25519(signer) => signer.signer().sign(message).into(),
+            Self::Ecdsa(signer) => signer.signer().sign(message).into(),
+        }
+    }
+
+    /// Returns the cryptography scheme of the signer.
+    pub fn scheme(&self) -> CryptoScheme {
+        match self {
+            Self::Sr25519(_) => CryptoScheme::Sr25519,
+            Self::Ed25519(_) => CryptoScheme::Ed25519,
+            Self::Ecdsa(_) => CryptoScheme::Ecdsa,
+        }
+    }
+}
+
+impl From<sr25519::Pair> for ChainXSigner {
+    fn from(pair: sr25519::Pair) -> Self {
+        Self::Sr25519(PairSigner::new(pair))
+    }
+}
+
+impl From<ed25519::Pair> for ChainXSigner {
+    fn from(pair: ed25519::Pair) -> Self {
+        Self::Ed25519(PairSigner::new(pair))
+    }
+}
+
+impl From<ecdsa::Pair> for ChainXSigner {
+    fn from(pair: ecdsa::Pair) -> Self {
+        Self::Ecdsa(PairSigner::new(pair))
+    }
+}
+
+impl Signer<ChainXRuntime> for ChainXSigner {
+    fn account_id(&self) -> &AccountId {
+        match self {
+            Self::Sr25519(signer) => signer.account_id(),
+            Self::Ed25519(signer) => signer.account_id(),
+            Self::Ecdsa(signer) => signer.account_id(),
+        }
+    }
+
+    fn nonce(&self) -> Option<Index> {
+        match self {
+            Self::Sr25519(signer) => signer.nonce(),
+            Self::Ed25519(signer) => signer.nonce(),
+            Self::Ecdsa(signer) => signer.nonce(),
+        }
+    }
+
+    fn sign(
+        &self,
+        extrinsic: SignedPayload<ChainXRuntime>,
+    ) -> Pin<
+        Box<dyn Future<Output = Result<UncheckedExtrinsic<ChainXRuntime>, String>> + Send + Sync>,
+    > {
+        match self {
+            Self::Sr25519(signer) => signer.sign(extrinsic),
+            Self::Ed25519(signer) => signer.sign(extrinsic),
+            Self::Ecdsa(signer) => signer.sign(extrinsic),
+        }
+    }
+}
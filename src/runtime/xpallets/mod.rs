@@ -1,3 +1,4 @@
 pub mod xassets;
+pub mod xassets_registrar;
 pub mod xmining_asset;
 pub mod xstaking;
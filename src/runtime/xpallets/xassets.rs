@@ -1,6 +1,7 @@
 use std::{collections::BTreeMap, marker::PhantomData};
 
 use codec::{Decode, Encode};
+use serde::Serialize;
 use subxt::{
     balances::{Balances, BalancesEventsDecoder},
     module,
@@ -67,7 +68,7 @@ pub struct TotalAssetBalanceStore<T: XAssets> {
 
 pub type BalanceOf<T> = <T as Balances>::Balance;
 
-#[derive(Clone, Copy, Debug, Ord, PartialOrd, Eq, PartialEq, Encode, Decode)]
+#[derive(Clone, Copy, Debug, Ord, PartialOrd, Eq, PartialEq, Encode, Decode, Serialize)]
 pub enum AssetType {
     Usable,
     Locked,
@@ -0,0 +1,45 @@
+use std::marker::PhantomData;
+
+use codec::{Decode, Encode};
+use subxt::{
+    module,
+    system::{System, SystemEventsDecoder},
+    Store,
+};
+
+use crate::runtime::primitives::AssetId;
+
+#[module]
+pub trait XAssetsRegistrar: System {}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+/// AssetInfoOf field of the `XAssetsRegistrar` module.
+#[derive(Clone, Debug, Eq, PartialEq, Store, Encode)]
+pub struct AssetInfoOfStore<T: XAssetsRegistrar> {
+    #[store(returns = Option<AssetInfo>)]
+    pub _runtime: PhantomData<T>,
+    pub asset_id: AssetId,
+}
+
+/// The chain the asset comes from.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Encode, Decode)]
+pub enum Chain {
+    ChainX,
+    Bitcoin,
+    Ethereum,
+    Polkadot,
+}
+
+/// Registered information of an asset.
+#[derive(Clone, Debug, Eq, PartialEq, Encode, Decode)]
+pub struct AssetInfo {
+    /// Symbol of the asset, e.g. `X-BTC`.
+    pub token: Vec<u8>,
+    pub token_name: Vec<u8>,
+    pub chain: Chain,
+    pub decimals: u8,
+    pub desc: Vec<u8>,
+}
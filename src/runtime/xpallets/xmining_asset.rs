@@ -1,6 +1,7 @@
-use std::marker::PhantomData;
+use std::{fmt::Display, marker::PhantomData};
 
 use codec::{Decode, Encode};
+use serde::Serialize;
 use subxt::{
     balances::{Balances, BalancesEventsDecoder},
     module,
@@ -8,7 +9,7 @@ use subxt::{
     Call, Event, Store,
 };
 
-use crate::runtime::primitives::AssetId;
+use crate::{runtime::primitives::AssetId, serde_num_str};
 
 #[module]
 pub trait XMiningAsset: Balances + System {}
@@ -63,18 +64,28 @@ pub struct MinerLedgersStore<'a, T: XMiningAsset> {
 pub type MiningWeight = u128;
 
 /// Vote weight properties of validator.
-#[derive(PartialEq, Eq, Clone, Default, Debug, Encode, Decode)]
+#[derive(PartialEq, Eq, Clone, Default, Debug, Encode, Decode, Serialize)]
+#[serde(
+    rename_all = "camelCase",
+    bound(serialize = "MiningWeight: Display, BlockNumber: Serialize")
+)]
 pub struct AssetLedger<MiningWeight, BlockNumber> {
     /// Last calculated total vote weight of current validator.
+    #[serde(with = "serde_num_str")]
     pub last_total_mining_weight: MiningWeight,
     /// Block number at which point `last_total_vote_weight` just updated.
     pub last_total_mining_weight_update: BlockNumber,
 }
 
 /// Mining weight properties of asset miners.
-#[derive(PartialEq, Eq, Clone, Debug, Default, Encode, Decode)]
+#[derive(PartialEq, Eq, Clone, Debug, Default, Encode, Decode, Serialize)]
+#[serde(
+    rename_all = "camelCase",
+    bound(serialize = "MiningWeight: Display, BlockNumber: Serialize")
+)]
 pub struct MinerLedger<MiningWeight, BlockNumber> {
     /// Last calculated total vote weight of current validator.
+    #[serde(with = "serde_num_str")]
     pub last_mining_weight: MiningWeight,
     /// Block number at which point `last_total_vote_weight` just updated.
     pub last_mining_weight_update: BlockNumber,
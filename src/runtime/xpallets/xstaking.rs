@@ -1,7 +1,8 @@
 use std::{
     collections::BTreeMap,
-    fmt::{self, Debug},
+    fmt::{self, Debug, Display},
     marker::PhantomData,
+    str::FromStr,
 };
 
 use codec::{Decode, Encode};
@@ -10,9 +11,11 @@ use subxt::{
     balances::{Balances, BalancesEventsDecoder},
     module,
     system::{System, SystemEventsDecoder},
-    Call, Store,
+    Call, Event, Store,
 };
 
+use crate::{serde_num_str, serde_text};
+
 #[module]
 pub trait XStaking: Balances + System {}
 
@@ -61,6 +64,15 @@ pub struct UnbondCall<'a, T: XStaking> {
     pub value: <T as Balances>::Balance,
 }
 
+#[derive(Clone, Debug, PartialEq, Call, Encode)]
+pub struct WithdrawUnbondedCall<'a, T: XStaking> {
+    /// Validator of the unbonded funds.
+    pub target: &'a <T as System>::Address,
+    /// Index of the chunk in `NominatorLedger::unbonded_chunks`.
+    #[codec(compact)]
+    pub unbonded_index: UnbondedIndex,
+}
+
 #[derive(Clone, Debug, PartialEq, Call, Encode)]
 pub struct ClaimCall<'a, T: XStaking> {
     /// Target of the claim.
@@ -100,6 +112,33 @@ pub struct SetSessionsPerEraCall<T: XStaking> {
 /// Simple index type with which we can count sessions.
 pub type SessionIndex = u32;
 
+/// Index of the unbonded chunk of a nominator.
+pub type UnbondedIndex = u32;
+
+// ============================================================================
+// Event
+// ============================================================================
+
+/// A staker claimed the staking dividend from a validator. [claimer, validator, amount]
+#[derive(Clone, Debug, PartialEq, Event, Decode)]
+pub struct ClaimedEvent<T: XStaking> {
+    /// claimer.
+    pub claimer: <T as System>::AccountId,
+    /// validator of the claimed dividend.
+    pub validator: <T as System>::AccountId,
+    /// amount
+    pub amount: <T as Balances>::Balance,
+}
+
+/// A staker withdrew the unbonded funds. [staker, amount]
+#[derive(Clone, Debug, PartialEq, Event, Decode)]
+pub struct WithdrawnEvent<T: XStaking> {
+    /// staker.
+    pub staker: <T as System>::AccountId,
+    /// amount
+    pub amount: <T as Balances>::Balance,
+}
+
 // ============================================================================
 // Storage
 // ============================================================================
@@ -139,7 +178,8 @@ pub type VoteWeight = u128;
 pub type ReferralId = Vec<u8>;
 
 /// Profile of staking validator.
-#[derive(PartialEq, Eq, Clone, Default, Encode, Decode)]
+#[derive(PartialEq, Eq, Clone, Default, Encode, Decode, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ValidatorProfile<BlockNumber> {
     /// Block number at which point it's registered on chain.
     pub registered_at: BlockNumber,
@@ -150,6 +190,7 @@ pub struct ValidatorProfile<BlockNumber> {
     /// Block number of last performed `chill` operation.
     pub last_chilled: Option<BlockNumber>,
     /// Referral identity that belongs to the validator.
+    #[serde(with = "serde_text")]
     pub referral_id: ReferralId,
 }
 
@@ -165,11 +206,17 @@ impl<BlockNumber: Debug> Debug for ValidatorProfile<BlockNumber> {
 }
 
 /// Vote weight properties of validator.
-#[derive(PartialEq, Eq, Clone, Default, Debug, Encode, Decode)]
+#[derive(PartialEq, Eq, Clone, Default, Debug, Encode, Decode, Serialize)]
+#[serde(
+    rename_all = "camelCase",
+    bound(serialize = "Balance: Display, VoteWeight: Display, BlockNumber: Serialize")
+)]
 pub struct ValidatorLedger<Balance, VoteWeight, BlockNumber> {
     /// The total amount of all the nominators' vote balances.
+    #[serde(with = "serde_num_str")]
     pub total_nomination: Balance,
     /// Last calculated total vote weight of current validator.
+    #[serde(with = "serde_num_str")]
     pub last_total_vote_weight: VoteWeight,
     /// Block number at which point `last_total_vote_weight` just updated.
     pub last_total_vote_weight_update: BlockNumber,
@@ -177,11 +224,19 @@ pub struct ValidatorLedger<Balance, VoteWeight, BlockNumber> {
 
 /// Vote weight properties of nominator.
 #[derive(PartialEq, Eq, Clone, Default, Debug, Encode, Decode, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(
+    rename_all = "camelCase",
+    bound(
+        serialize = "Balance: Display, VoteWeight: Display, BlockNumber: Serialize",
+        deserialize = "Balance: FromStr, VoteWeight: FromStr, BlockNumber: Deserialize<'de>"
+    )
+)]
 pub struct NominatorLedger<Balance, VoteWeight, BlockNumber> {
     /// The amount of vote.
+    #[serde(with = "serde_num_str")]
     pub nomination: Balance,
     /// Last calculated total vote weight of current nominator.
+    #[serde(with = "serde_num_str")]
     pub last_vote_weight: VoteWeight,
     /// Block number at which point `last_vote_weight` just updated.
     pub last_vote_weight_update: BlockNumber,
@@ -191,16 +246,23 @@ pub struct NominatorLedger<Balance, VoteWeight, BlockNumber> {
 
 /// Type for noting when the unbonded fund can be withdrawn.
 #[derive(PartialEq, Eq, Clone, Debug, Encode, Decode, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(
+    rename_all = "camelCase",
+    bound(
+        serialize = "Balance: Display, BlockNumber: Serialize",
+        deserialize = "Balance: FromStr, BlockNumber: Deserialize<'de>"
+    )
+)]
 pub struct Unbonded<Balance, BlockNumber> {
     /// Amount of funds to be unlocked.
+    #[serde(with = "serde_num_str")]
     pub value: Balance,
     /// Block number at which point it'll be unlocked.
     pub locked_until: BlockNumber,
 }
 
 /// Detailed types of reserved balances in Staking.
-#[derive(PartialEq, PartialOrd, Ord, Eq, Clone, Copy, Encode, Decode, Debug)]
+#[derive(PartialEq, PartialOrd, Ord, Eq, Clone, Copy, Encode, Decode, Debug, Serialize)]
 pub enum LockedType {
     /// Locked balances when nominator calls `bond`.
     Bonded,
@@ -0,0 +1,278 @@
+//! State files for the offline analysis.
+//!
+//! A state file contains the storage of one or more blocks dumped from a node, `Rpc` serves
+//! the storage queries from it with `--state` instead of a live node.
+
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufReader, BufWriter},
+    ops::Bound,
+    path::Path,
+};
+
+use anyhow::{anyhow, Result};
+use futures::{future::BoxFuture, TryStreamExt};
+use jsonrpsee::common::Params;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use sp_core::storage::{StorageChangeSet, StorageData, StorageKey};
+use sp_runtime::traits::Header as _;
+use subxt::system::System;
+
+use crate::{
+    backend::Backend,
+    rpc::Rpc,
+    runtime::{
+        primitives::{BlockNumber, Hash},
+        ChainXRuntime,
+    },
+};
+
+type Header = <ChainXRuntime as System>::Header;
+
+/// The storage of a block.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockState {
+    pub hash: Hash,
+    pub header: Header,
+    /// The hex encoded storage keys and values.
+    #[serde(with = "serde_hex_map")]
+    pub storage: BTreeMap<Vec<u8>, Vec<u8>>,
+}
+
+/// The storage of the blocks dumped by `chainx-cli state dump`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StateFile {
+    pub genesis_hash: Hash,
+    /// Sorted by the block number, the last one is served as the latest block.
+    pub blocks: Vec<BlockState>,
+}
+
+impl StateFile {
+    /// Fetches the storage under `prefixes` of `blocks` from the node.
+    ///
+    /// The latest block is fetched if `blocks` is empty, the full storage is fetched if
+    /// `prefixes` is empty.
+    pub async fn dump(rpc: &Rpc, blocks: &[BlockNumber], prefixes: &[StorageKey]) -> Result<Self> {
+        let numbers = if blocks.is_empty() {
+            vec![None]
+        } else {
+            blocks.iter().copied().map(Some).collect()
+        };
+        let prefixes = if prefixes.is_empty() {
+            vec![StorageKey(Vec::new())]
+        } else {
+            prefixes.to_vec()
+        };
+
+        let mut states = Vec::with_capacity(numbers.len());
+        for number in numbers {
+            let hash = rpc
+                .block_hash(number)
+                .await?
+                .ok_or_else(|| anyhow!("Block #{:?} not found", number))?;
+            let header = rpc
+                .header(Some(hash))
+                .await?
+                .ok_or_else(|| anyhow!("Header of block {:?} not found", hash))?;
+            let mut storage = BTreeMap::new();
+            for prefix in &prefixes {
+                let mut pairs = rpc.iter_raw_storage(prefix.clone(), Some(hash));
+                while let Some((key, data)) = pairs.try_next().await? {
+                    storage.insert(key.0, data.0);
+                }
+            }
+            states.push(BlockState {
+                hash,
+                header,
+                storage,
+            });
+        }
+        states.sort_by_key(|state| state.header.number);
+        states.dedup_by_key(|state| state.hash);
+
+        Ok(Self {
+            genesis_hash: rpc.genesis_hash().await?,
+            blocks: states,
+        })
+    }
+
+    /// Returns the state of a genesis block with `storage`, a fixture of the tests.
+    pub fn genesis(storage: BTreeMap<Vec<u8>, Vec<u8>>) -> Self {
+        let header = Header::new(
+            0,
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+        );
+        let hash = header.hash();
+        Self {
+            genesis_hash: hash,
+            blocks: vec![BlockState {
+                hash,
+                header,
+                storage,
+            }],
+        }
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let file = File::open(path.as_ref()).map_err(|err| {
+            anyhow!(
+                "Failed to open state file {}: {}",
+                path.as_ref().display(),
+                err
+            )
+        })?;
+        let state: Self = serde_json::from_reader(BufReader::new(file))?;
+        if state.blocks.is_empty() {
+            return Err(anyhow!("State file {} is empty", path.as_ref().display()));
+        }
+        Ok(state)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let file = File::create(path)?;
+        serde_json::to_writer(BufWriter::new(file), self)?;
+        Ok(())
+    }
+
+    fn latest(&self) -> &BlockState {
+        self.blocks
+            .last()
+            .expect("State file has at least one block; qed")
+    }
+
+    /// Returns the state of block `hash`, the latest one if `hash` is `None`.
+    fn at(&self, hash: Option<Hash>) -> Result<&BlockState> {
+        match hash {
+            None => Ok(self.latest()),
+            Some(hash) => self
+                .blocks
+                .iter()
+                .find(|state| state.hash == hash)
+                .ok_or_else(|| anyhow!("Block {:?} is not in the state file", hash)),
+        }
+    }
+
+    /// Handles the JSON-RPC request of `method` with the state.
+    ///
+    /// Only the chain and storage queries are supported.
+    pub fn handle(&self, method: &str, params: Params) -> Result<JsonValue> {
+        let params = match params {
+            Params::None => Vec::new(),
+            Params::Array(params) => params,
+            Params::Map(_) => return Err(anyhow!("Named params are not supported")),
+        };
+        let param = |index: usize| params.get(index).cloned().unwrap_or(JsonValue::Null);
+
+        match method {
+            "chain_getBlockHash" => {
+                let number: Option<BlockNumber> = serde_json::from_value(param(0))?;
+                let hash = match number {
+                    None => Some(self.latest().hash),
+                    Some(0) => Some(self.genesis_hash),
+                    Some(number) => self
+                        .blocks
+                        .iter()
+                        .find(|state| state.header.number == number)
+                        .map(|state| state.hash),
+                };
+                Ok(serde_json::to_value(hash)?)
+            }
+            "chain_getFinalizedHead" => Ok(serde_json::to_value(self.latest().hash)?),
+            "chain_getHeader" => {
+                let state = self.at(serde_json::from_value(param(0))?)?;
+                Ok(serde_json::to_value(&state.header)?)
+            }
+            "state_getStorage" => {
+                let key: StorageKey = serde_json::from_value(param(0))?;
+                let state = self.at(serde_json::from_value(param(1))?)?;
+                let data = state.storage.get(&key.0).cloned().map(StorageData);
+                Ok(serde_json::to_value(data)?)
+            }
+            "state_getKeysPaged" => {
+                let prefix: StorageKey = serde_json::from_value(param(0))?;
+                let count: u32 = serde_json::from_value(param(1))?;
+                let start_key: Option<StorageKey> = serde_json::from_value(param(2))?;
+                let state = self.at(serde_json::from_value(param(3))?)?;
+                let start = match start_key {
+                    Some(start_key) => Bound::Excluded(start_key.0),
+                    None => Bound::Included(prefix.0.clone()),
+                };
+                let keys = state
+                    .storage
+                    .range((start, Bound::Unbounded))
+                    .map(|(key, _)| key)
+                    .take_while(|key| key.starts_with(&prefix.0))
+                    .take(count as usize)
+                    .map(|key| StorageKey(key.clone()))
+                    .collect::<Vec<_>>();
+                Ok(serde_json::to_value(keys)?)
+            }
+            "state_queryStorageAt" => {
+                let keys: Vec<StorageKey> = serde_json::from_value(param(0))?;
+                let state = self.at(serde_json::from_value(param(1))?)?;
+                let changes = keys
+                    .into_iter()
+                    .map(|key| {
+                        let data = state.storage.get(&key.0).cloned().map(StorageData);
+                        (key, data)
+                    })
+                    .collect();
+                let change_set = StorageChangeSet {
+                    block: state.hash,
+                    changes,
+                };
+                Ok(serde_json::to_value(vec![change_set])?)
+            }
+            _ => Err(anyhow!(
+                "`{}` is not supported with `--state`, a live node is required",
+                method
+            )),
+        }
+    }
+}
+
+impl Backend for StateFile {
+    fn request<'a>(&'a self, method: &'a str, params: Params) -> BoxFuture<'a, Result<JsonValue>> {
+        Box::pin(async move { self.handle(method, params) })
+    }
+}
+
+/// Hex serialization/deserialization of the storage map.
+mod serde_hex_map {
+    use std::collections::BTreeMap;
+
+    use serde::{de, ser::SerializeMap, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        map: &BTreeMap<Vec<u8>, Vec<u8>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_map(Some(map.len()))?;
+        for (key, value) in map {
+            state.serialize_entry(
+                &format!("0x{}", hex::encode(key)),
+                &format!("0x{}", hex::encode(value)),
+            )?;
+        }
+        state.end()
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<BTreeMap<Vec<u8>, Vec<u8>>, D::Error> {
+        let decode = |data: &str| -> Result<Vec<u8>, D::Error> {
+            hex::decode(data.trim_start_matches("0x")).map_err(de::Error::custom)
+        };
+        BTreeMap::<String, String>::deserialize(deserializer)?
+            .iter()
+            .map(|(key, value)| Ok((decode(key)?, decode(value)?)))
+            .collect()
+    }
+}
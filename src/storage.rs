@@ -0,0 +1,230 @@
+//! Typed storage keys of the storage maps.
+//!
+//! A key of a storage map is laid out as
+//! `twox_128(module) ++ twox_128(storage) ++ hasher(key1) [++ hasher(key2)]`,
+//! where the hashers ending with `Concat` append the SCALE encoded key to the hash.
+
+use std::marker::PhantomData;
+
+use anyhow::{anyhow, Result};
+use codec::{Decode, Encode};
+use sp_core::{blake2_128, storage::StorageKey, twox_128, twox_64};
+
+/// Returns `twox_128(module) ++ twox_128(storage_name)`.
+pub fn storage_prefix_for(module: &str, storage_name: &str) -> Vec<u8> {
+    let mut storage_prefix = twox_128(module.as_bytes()).to_vec();
+    storage_prefix.extend_from_slice(&twox_128(storage_name.as_bytes()));
+    storage_prefix
+}
+
+/// A hasher of the storage map keys whose output contains the raw key.
+pub trait StorageHasher {
+    /// Length of the hash before the encoded key.
+    const HASH_LEN: usize;
+
+    /// Returns the hash before the encoded key.
+    fn hash(encoded_key: &[u8]) -> Vec<u8>;
+
+    /// Decodes the key from the hashed key at the start of `input`, and advances `input`.
+    fn decode_key<K: Decode>(input: &mut &[u8]) -> Result<K> {
+        if input.len() < Self::HASH_LEN {
+            return Err(anyhow!("Storage key is too short for the hasher"));
+        }
+        let (hash, mut rest) = input.split_at(Self::HASH_LEN);
+        let key = K::decode(&mut rest)?;
+        let encoded_key = &input[Self::HASH_LEN..input.len() - rest.len()];
+        if Self::hash(encoded_key) != hash {
+            return Err(anyhow!(
+                "Storage key hash mismatch, has the hasher changed?"
+            ));
+        }
+        *input = rest;
+        Ok(key)
+    }
+}
+
+/// `blake2_128(key) ++ key`
+pub struct Blake2_128Concat;
+
+impl StorageHasher for Blake2_128Concat {
+    const HASH_LEN: usize = 16;
+
+    fn hash(encoded_key: &[u8]) -> Vec<u8> {
+        blake2_128(encoded_key).to_vec()
+    }
+}
+
+/// `twox_64(key) ++ key`
+pub struct Twox64Concat;
+
+impl StorageHasher for Twox64Concat {
+    const HASH_LEN: usize = 8;
+
+    fn hash(encoded_key: &[u8]) -> Vec<u8> {
+        twox_64(encoded_key).to_vec()
+    }
+}
+
+/// `key`
+pub struct Identity;
+
+impl StorageHasher for Identity {
+    const HASH_LEN: usize = 0;
+
+    fn hash(_encoded_key: &[u8]) -> Vec<u8> {
+        Vec::new()
+    }
+}
+
+fn strip_prefix<'a>(prefix: &[u8], key: &'a StorageKey) -> Result<&'a [u8]> {
+    if key.0.starts_with(prefix) {
+        Ok(&key.0[prefix.len()..])
+    } else {
+        Err(anyhow!(
+            "Storage key 0x{} has a wrong prefix",
+            hex::encode(&key.0)
+        ))
+    }
+}
+
+fn ensure_consumed(input: &[u8]) -> Result<()> {
+    if input.is_empty() {
+        Ok(())
+    } else {
+        Err(anyhow!("Storage key has {} trailing bytes", input.len()))
+    }
+}
+
+/// A storage map `K => V` hashed with `H`.
+pub struct StorageMap<H, K, V> {
+    pub module: &'static str,
+    pub name: &'static str,
+    _marker: PhantomData<(H, K, V)>,
+}
+
+impl<H, K, V> StorageMap<H, K, V> {
+    pub const fn new(module: &'static str, name: &'static str) -> Self {
+        Self {
+            module,
+            name,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<H: StorageHasher, K: Encode + Decode, V> StorageMap<H, K, V> {
+    pub fn prefix(&self) -> StorageKey {
+        StorageKey(storage_prefix_for(self.module, self.name))
+    }
+
+    pub fn key(&self, key: &K) -> StorageKey {
+        let mut storage_key = self.prefix().0;
+        let encoded = key.encode();
+        storage_key.extend(H::hash(&encoded));
+        storage_key.extend(encoded);
+        StorageKey(storage_key)
+    }
+
+    pub fn decode_key(&self, key: &StorageKey) -> Result<K> {
+        let prefix = self.prefix();
+        let mut input = strip_prefix(&prefix.0, key)?;
+        let key = H::decode_key(&mut input)?;
+        ensure_consumed(input)?;
+        Ok(key)
+    }
+}
+
+/// A storage double map `K1, K2 => V` hashed with `H1` and `H2`.
+pub struct StorageDoubleMap<H1, K1, H2, K2, V> {
+    pub module: &'static str,
+    pub name: &'static str,
+    _marker: PhantomData<(H1, K1, H2, K2, V)>,
+}
+
+impl<H1, K1, H2, K2, V> StorageDoubleMap<H1, K1, H2, K2, V> {
+    pub const fn new(module: &'static str, name: &'static str) -> Self {
+        Self {
+            module,
+            name,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<H1, K1, H2, K2, V> StorageDoubleMap<H1, K1, H2, K2, V>
+where
+    H1: StorageHasher,
+    K1: Encode + Decode,
+    H2: StorageHasher,
+    K2: Encode + Decode,
+{
+    pub fn prefix(&self) -> StorageKey {
+        StorageKey(storage_prefix_for(self.module, self.name))
+    }
+
+    pub fn key(&self, key1: &K1, key2: &K2) -> StorageKey {
+        let mut storage_key = self.prefix().0;
+        let (encoded1, encoded2) = (key1.encode(), key2.encode());
+        storage_key.extend(H1::hash(&encoded1));
+        storage_key.extend(encoded1);
+        storage_key.extend(H2::hash(&encoded2));
+        storage_key.extend(encoded2);
+        StorageKey(storage_key)
+    }
+
+    pub fn decode_key(&self, key: &StorageKey) -> Result<(K1, K2)> {
+        let prefix = self.prefix();
+        let mut input = strip_prefix(&prefix.0, key)?;
+        let key1 = H1::decode_key(&mut input)?;
+        let key2 = H2::decode_key(&mut input)?;
+        ensure_consumed(input)?;
+        Ok((key1, key2))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sp_core::crypto::AccountId32;
+
+    #[test]
+    fn test_decode_map_key() {
+        let map = StorageMap::<Blake2_128Concat, AccountId32, ()>::new("System", "Account");
+        let who = AccountId32::from([7u8; 32]);
+        let key = map.key(&who);
+        assert_eq!(key.0.len(), 32 + 16 + 32);
+        assert_eq!(map.decode_key(&key).unwrap(), who);
+
+        let map = StorageMap::<Identity, u32, ()>::new("Foo", "Bar");
+        assert_eq!(map.decode_key(&map.key(&42)).unwrap(), 42);
+    }
+
+    #[test]
+    fn test_decode_double_map_key() {
+        let map = StorageDoubleMap::<Blake2_128Concat, AccountId32, Twox64Concat, u32, ()>::new(
+            "XAssets",
+            "AssetBalance",
+        );
+        let who = AccountId32::from([1u8; 32]);
+        let key = map.key(&who, &1);
+        assert_eq!(map.decode_key(&key).unwrap(), (who, 1));
+    }
+
+    #[test]
+    fn test_decode_key_errors() {
+        let map = StorageMap::<Twox64Concat, u32, ()>::new("XAssets", "TotalAssetBalance");
+        let mut key = map.key(&1);
+
+        // Hashed with another hasher.
+        let other = StorageMap::<Blake2_128Concat, u32, ()>::new("XAssets", "TotalAssetBalance");
+        assert!(other.decode_key(&key).is_err());
+
+        // Another storage.
+        let other = StorageMap::<Twox64Concat, u32, ()>::new("XAssets", "AssetBalance");
+        assert!(other.decode_key(&key).is_err());
+
+        // Trailing bytes.
+        key.0.push(0);
+        assert!(map.decode_key(&key).is_err());
+    }
+}
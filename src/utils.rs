@@ -1,4 +1,4 @@
-use std::{fs::File, io::Read, path::Path};
+use std::{convert::TryFrom, fs::File, io::Read, path::Path};
 
 use anyhow::{anyhow, Result};
 use sp_core::crypto::{Pair, Public, Ss58Codec};
@@ -6,9 +6,12 @@ use sp_keyring::AccountKeyring;
 use sp_runtime::traits::{IdentifyAccount, Verify};
 use subxt::ClientBuilder;
 
-use crate::runtime::{
-    primitives::{AccountId, BlockNumber, Hash, Signature},
-    ChainXClient, ChainXRuntime,
+use crate::{
+    connection::ConnectionOptions,
+    runtime::{
+        primitives::{AccountId, BlockNumber, Hash, Signature},
+        ChainXClient, ChainXRuntime,
+    },
 };
 
 pub fn read_code<P: AsRef<Path>>(code_path: P) -> Result<Vec<u8>> {
@@ -19,6 +22,8 @@ pub fn read_code<P: AsRef<Path>>(code_path: P) -> Result<Vec<u8>> {
 }
 
 /// Parses AccountId from String, also supports passing the test accounts directly.
+///
+/// The address can be either a SS58 address or a `0x`-prefixed hex public key.
 pub fn parse_account(address: &str) -> Result<AccountId> {
     match String::from(address).to_lowercase().as_str() {
         "alice" => Ok(AccountKeyring::Alice.to_account_id()),
@@ -29,6 +34,11 @@ pub fn parse_account(address: &str) -> Result<AccountId> {
         "ferdie" => Ok(AccountKeyring::Ferdie.to_account_id()),
         "one" => Ok(AccountKeyring::One.to_account_id()),
         "two" => Ok(AccountKeyring::Two.to_account_id()),
+        _ if address.starts_with("0x") => {
+            let public = hex::decode(&address[2..])?;
+            Ok(AccountId::try_from(public.as_slice())
+                .map_err(|_| anyhow!("Invalid public key length: {}", public.len()))?)
+        }
         _ => Ok(AccountId::from_string(address)
             .map_err(|err| anyhow!("Failed to parse account address: {:?}", err))?),
     }
@@ -51,12 +61,23 @@ where
     AccountPublic::from(get_from_seed::<TPublic>(seed)).into_account()
 }
 
-/// Builds a ChainX runtime specific client.
-pub async fn build_client<U: Into<String>>(url: U) -> Result<ChainXClient> {
-    Ok(ClientBuilder::<ChainXRuntime>::new()
-        .set_url(url)
-        .build()
-        .await?)
+/// Builds a ChainX runtime specific client over the transport of the url scheme, retries the
+/// connection according to `options`.
+pub async fn build_client(options: &ConnectionOptions) -> Result<ChainXClient> {
+    if options.state.is_some() {
+        return Err(anyhow!(
+            "A live node is required, `--state` is not supported"
+        ));
+    }
+    options
+        .connect(|| async {
+            let client = options.rpc_client().await?;
+            Ok(ClientBuilder::<ChainXRuntime>::new()
+                .set_client(client)
+                .build()
+                .await?)
+        })
+        .await
 }
 
 pub async fn block_hash(
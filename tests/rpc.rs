@@ -0,0 +1,199 @@
+use std::{
+    collections::BTreeMap,
+    net::TcpListener,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use anyhow::{anyhow, Result};
+use codec::Encode;
+use futures::future::BoxFuture;
+use jsonrpsee::common::Params;
+use serde_json::Value as JsonValue;
+
+use chainx_cli::{
+    backend::{serve_ws, Backend},
+    connection::{ConnectionOptions, TransportError},
+    rpc::{Rpc, ASSET_BALANCE, MINER_LEDGERS, NOMINATIONS},
+    runtime::{
+        primitives::{AccountId, AssetId, Balance, BlockNumber},
+        xpallets::{xassets::AssetType, xmining_asset::MinerLedger, xstaking::NominatorLedger},
+    },
+    state::StateFile,
+};
+
+const X_BTC: AssetId = 1;
+
+fn alice() -> AccountId {
+    AccountId::from([1u8; 32])
+}
+
+fn bob() -> AccountId {
+    AccountId::from([2u8; 32])
+}
+
+/// Returns the storage of the genesis fixture, SCALE encoded.
+fn fixtures() -> BTreeMap<Vec<u8>, Vec<u8>> {
+    let balance = |usable: Balance, locked: Balance| {
+        vec![(AssetType::Usable, usable), (AssetType::Locked, locked)]
+            .into_iter()
+            .collect::<BTreeMap<_, _>>()
+    };
+    let nomination =
+        |nomination: Balance, weight: u128| NominatorLedger::<Balance, u128, BlockNumber> {
+            nomination,
+            last_vote_weight: weight,
+            ..Default::default()
+        };
+    let miner_ledger = |weight: u128| MinerLedger::<u128, BlockNumber> {
+        last_mining_weight: weight,
+        last_mining_weight_update: 1,
+        last_claim: Some(1),
+    };
+
+    vec![
+        (
+            ASSET_BALANCE.key(&alice(), &X_BTC),
+            balance(100, 20).encode(),
+        ),
+        (ASSET_BALANCE.key(&bob(), &X_BTC), balance(300, 0).encode()),
+        (ASSET_BALANCE.key(&bob(), &8888), balance(5, 0).encode()),
+        (
+            NOMINATIONS.key(&alice(), &bob()),
+            nomination(10, 1000).encode(),
+        ),
+        (
+            NOMINATIONS.key(&bob(), &bob()),
+            nomination(20, 2000).encode(),
+        ),
+        (
+            NOMINATIONS.key(&bob(), &alice()),
+            nomination(30, 3000).encode(),
+        ),
+        (
+            MINER_LEDGERS.key(&alice(), &X_BTC),
+            miner_ledger(7).encode(),
+        ),
+        (MINER_LEDGERS.key(&bob(), &X_BTC), miner_ledger(9).encode()),
+    ]
+    .into_iter()
+    .map(|(key, value)| (key.0, value))
+    .collect()
+}
+
+fn state_backend() -> Arc<dyn Backend> {
+    Arc::new(StateFile::genesis(fixtures()))
+}
+
+fn mock_rpc() -> Rpc {
+    let options = ConnectionOptions::new("ws://127.0.0.1:8087");
+    Rpc::with_backend(state_backend(), &options).unwrap()
+}
+
+#[async_std::test]
+async fn test_get_asset_balance() {
+    let rpc = mock_rpc();
+    let asset_balance = rpc.get_asset_balance(None).await.unwrap();
+
+    assert_eq!(asset_balance.len(), 2);
+    assert_eq!(asset_balance[&alice()][&X_BTC][&AssetType::Usable], 100);
+    assert_eq!(asset_balance[&alice()][&X_BTC][&AssetType::Locked], 20);
+    assert_eq!(asset_balance[&bob()].len(), 2);
+    assert_eq!(asset_balance[&bob()][&8888][&AssetType::Usable], 5);
+}
+
+#[async_std::test]
+async fn test_get_nominations() {
+    let rpc = mock_rpc();
+    let nominations = rpc.get_nominations(None).await.unwrap();
+
+    assert_eq!(nominations[&alice()].len(), 1);
+    assert_eq!(nominations[&alice()][&bob()].nomination, 10);
+    assert_eq!(nominations[&bob()].len(), 2);
+    assert_eq!(nominations[&bob()][&alice()].last_vote_weight, 3000);
+}
+
+#[async_std::test]
+async fn test_get_miner_ledgers() {
+    let rpc = mock_rpc();
+    let genesis_hash = rpc.genesis_hash().await.unwrap();
+    let miner_ledgers = rpc.get_miner_ledgers(Some(genesis_hash)).await.unwrap();
+
+    assert_eq!(miner_ledgers.len(), 2);
+    assert_eq!(miner_ledgers[&alice()][&X_BTC].last_mining_weight, 7);
+    assert_eq!(miner_ledgers[&bob()][&X_BTC].last_claim, Some(1));
+}
+
+#[async_std::test]
+async fn test_ws_stand_in_node() {
+    let addr = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let methods = [
+        "chain_getBlockHash",
+        "chain_getHeader",
+        "state_getKeysPaged",
+        "state_queryStorageAt",
+    ];
+    async_std::task::spawn(async move {
+        serve_ws(state_backend(), &addr, &methods).await.unwrap();
+    });
+
+    // The server may not be listening yet, the connection is retried.
+    let mut options = ConnectionOptions::new(format!("ws://{}", addr));
+    options.retry_backoff = 100;
+    let rpc = Rpc::new(&options).await.unwrap();
+
+    assert_eq!(
+        rpc.get_asset_balance(None).await.unwrap(),
+        mock_rpc().get_asset_balance(None).await.unwrap()
+    );
+}
+
+/// A live node stand-in failing every request, by the transport or with an error response.
+struct FailingBackend {
+    transport: bool,
+    requests: AtomicUsize,
+}
+
+impl Backend for FailingBackend {
+    fn request<'a>(
+        &'a self,
+        _method: &'a str,
+        _params: Params,
+    ) -> BoxFuture<'a, Result<JsonValue>> {
+        self.requests.fetch_add(1, Ordering::SeqCst);
+        let transport = self.transport;
+        Box::pin(async move {
+            if transport {
+                Err(TransportError("Connection reset".into()).into())
+            } else {
+                Err(anyhow!("Unknown storage"))
+            }
+        })
+    }
+
+    fn retryable(&self) -> bool {
+        true
+    }
+}
+
+#[async_std::test]
+async fn test_retry_transport_errors_only() {
+    let mut options = ConnectionOptions::new("ws://127.0.0.1:8087");
+    options.max_retries = 2;
+    options.retry_backoff = 1;
+
+    for &(transport, requests) in &[(true, 3), (false, 1)] {
+        let backend = Arc::new(FailingBackend {
+            transport,
+            requests: AtomicUsize::new(0),
+        });
+        let rpc = Rpc::with_backend(backend.clone(), &options).unwrap();
+        assert!(rpc.genesis_hash().await.is_err());
+        assert_eq!(backend.requests.load(Ordering::SeqCst), requests);
+    }
+}